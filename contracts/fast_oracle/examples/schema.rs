@@ -3,7 +3,13 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 use forecast_deliverdao::fast_oracle::msg::{
-    ExecuteMsg, InstantiateMsg, QueryMsg,
+    CallbackMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+};
+use forecast_deliverdao::fast_oracle::response::{
+    AllPricesResponse, CandlesResponse, ConfigResponse, FeedInfoResponse,
+    FeedersResponse, OwnerResponse, PendingOwnerResponse, PendingPriceResponse,
+    PriceAtResponse, PriceHistoryResponse, PriceResponse, PublisherKeyResponse,
+    SubscribersResponse, TwapResponse,
 };
 
 fn main() {
@@ -18,4 +24,20 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
+    export_schema(&schema_for!(CallbackMsg), &out_dir);
+    export_schema(&schema_for!(PriceResponse), &out_dir);
+    export_schema(&schema_for!(FeedInfoResponse), &out_dir);
+    export_schema(&schema_for!(AllPricesResponse), &out_dir);
+    export_schema(&schema_for!(PriceAtResponse), &out_dir);
+    export_schema(&schema_for!(PriceHistoryResponse), &out_dir);
+    export_schema(&schema_for!(TwapResponse), &out_dir);
+    export_schema(&schema_for!(CandlesResponse), &out_dir);
+    export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(OwnerResponse), &out_dir);
+    export_schema(&schema_for!(PendingOwnerResponse), &out_dir);
+    export_schema(&schema_for!(FeedersResponse), &out_dir);
+    export_schema(&schema_for!(PendingPriceResponse), &out_dir);
+    export_schema(&schema_for!(PublisherKeyResponse), &out_dir);
+    export_schema(&schema_for!(SubscribersResponse), &out_dir);
 }
@@ -1,14 +1,42 @@
 use crate::error::ContractError;
-use crate::state::{ADMIN, PRICE};
+use crate::state::{
+    ADMIN, CONFIG, FEEDERS, FEEDS, LAST_SIGNED, LEGACY_PRICE,
+    NEXT_SUBSCRIBER_ID, PENDING_OWNER, PENDING_PRICES, PRICES,
+    PRICE_CUMULATIVES, PRICE_HISTORY, PUBLISHER_KEYS, SUBMISSIONS, SUBSCRIBERS,
+};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
-    Uint128,
+    from_slice, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Empty, Env,
+    Event, MessageInfo, Order, Reply, Response, StdError, StdResult, Storage,
+    SubMsg, SubMsgResult, Timestamp, Uint128, Uint256, WasmMsg,
 };
+use cw_storage_plus::Bound;
 use forecast_deliverdao::fast_oracle::msg::{
-    ExecuteMsg, InstantiateMsg, QueryMsg,
+    CallbackMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+};
+use forecast_deliverdao::fast_oracle::response::{
+    AllPricesResponse, Candle, CandlesResponse, ConfigResponse,
+    FeedInfoResponse, FeedersResponse, OwnerResponse, PendingOwnerResponse,
+    PendingPriceResponse, PriceAtResponse, PriceHistoryResponse, PriceResponse,
+    PublisherKeyResponse, SubscribersResponse, TickerPrice, TwapResponse,
+};
+use forecast_deliverdao::fast_oracle::{
+    Config, FeedInfo, KeyScheme, PendingPrice, PriceEntry, PricePayload,
+    PublisherKey, Subscriber, DEFAULT_TICKER,
 };
+use semver::Version;
+use sha2::{Digest, Sha256};
+use std::convert::TryFrom;
+
+const CONTRACT_NAME: &str = "deliverdao:fast_oracle";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+/* Gas a subscriber callback gets unless it was subscribed with a limit */
+const DEFAULT_CALLBACK_GAS_LIMIT: u64 = 300_000;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -17,59 +45,1258 @@ pub fn instantiate(
     info: MessageInfo,
     _msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    PRICE.save(deps.storage, &0u128)?;
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     ADMIN.save(deps.storage, &info.sender)?;
+    /* The instantiator starts as the only feeder */
+    init_feeders(deps.storage, &info.sender)?;
 
     Ok(Response::new())
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut,
+    env: Env,
+    _msg: MigrateMsg,
+) -> Result<Response, ContractError> {
+    /* Oracles instantiated before cw2 was added have no version stored */
+    let stored_version = match cw2::CONTRACT.may_load(deps.storage)? {
+        Some(version) => {
+            if version.contract != CONTRACT_NAME {
+                return Err(ContractError::Std(StdError::generic_err(
+                    "Can only upgrade from same type",
+                )));
+            }
+            parse_version(&version.version)?
+        }
+        None => Version::new(0, 0, 0),
+    };
+
+    let new_version = parse_version(CONTRACT_VERSION)?;
+    if stored_version > new_version {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "Cannot downgrade from {} to {}",
+            stored_version, new_version
+        ))));
+    }
+
+    /* Handlers run in order, each one for the versions before it */
+    if stored_version < Version::new(0, 2, 0) {
+        migrate_legacy_price(deps.storage, &env)?;
+    }
+
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_attribute("previous_version", stored_version.to_string())
+        .add_attribute("new_version", new_version.to_string()))
+}
+
+/*
+ * The admin becomes the only feeder and its single price the default
+ * ticker's submission
+ */
+fn migrate_legacy_price(storage: &mut dyn Storage, env: &Env) -> StdResult<()> {
+    let admin = match ADMIN.may_load(storage)? {
+        Some(admin) => admin,
+        None => return Ok(()),
+    };
+    init_feeders(storage, &admin)?;
+
+    /* Oracles used to start from a zero price */
+    if let Some(price) = LEGACY_PRICE.may_load(storage)?.filter(|p| *p != 0) {
+        submit_price(
+            storage,
+            env,
+            &admin,
+            DEFAULT_TICKER,
+            Uint128::from(price),
+            env.block.time,
+        )?;
+        LEGACY_PRICE.remove(storage);
+    }
+
+    Ok(())
+}
+
+fn init_feeders(storage: &mut dyn Storage, feeder: &Addr) -> StdResult<()> {
+    FEEDERS.save(storage, feeder, &Empty {})?;
+    CONFIG.save(
+        storage,
+        &Config {
+            quorum: 1,
+            max_submission_age_seconds: Uint128::zero(),
+            max_deviation: Decimal::zero(),
+            min_update_interval_seconds: Uint128::zero(),
+        },
+    )
+}
+
+fn parse_version(version: &str) -> StdResult<Version> {
+    version
+        .parse()
+        .map_err(|e: semver::Error| StdError::generic_err(e.to_string()))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
-    if ADMIN.load(deps.storage)? != info.sender {
+    /* Feeders post prices, everything else is up to the admin */
+    let is_admin = ADMIN.load(deps.storage)? == info.sender;
+    let authorized = match &msg {
+        ExecuteMsg::Update { .. } => FEEDERS.has(deps.storage, &info.sender),
+        ExecuteMsg::AcceptOwnership {} => {
+            PENDING_OWNER.may_load(deps.storage)?.as_ref() == Some(&info.sender)
+        }
+        ExecuteMsg::ConfirmPrice { .. } => {
+            is_admin || FEEDERS.has(deps.storage, &info.sender)
+        }
+        /* Trust sits in the publisher key, not in the relayer */
+        ExecuteMsg::SubmitSigned { .. } => true,
+        _ => is_admin,
+    };
+    if !authorized {
         return Err(ContractError::Unauthorized {});
     }
 
     match msg {
-        ExecuteMsg::Update { price } => execute_set_price(deps, price),
-        ExecuteMsg::Owner { owner } => {
-            execute_set_owner(deps, owner.to_string())
+        ExecuteMsg::Update { ticker, price } => {
+            let time = env.block.time;
+            execute_set_price(deps, env, info.sender, ticker, price, time)
+        }
+        ExecuteMsg::ProposeOwner { owner } => {
+            execute_propose_owner(deps, owner.to_string())
+        }
+        ExecuteMsg::AcceptOwnership {} => execute_accept_ownership(deps, info),
+        ExecuteMsg::CancelOwnershipProposal {} => {
+            PENDING_OWNER.remove(deps.storage);
+            Ok(Response::new().add_event(
+                Event::new("forecast_deliverdao")
+                    .add_attribute("ownership_proposal_cancelled", "true"),
+            ))
+        }
+        ExecuteMsg::AddFeeder { feeder } => {
+            execute_add_feeder(deps, feeder.to_string())
+        }
+        ExecuteMsg::RemoveFeeder { feeder } => {
+            execute_remove_feeder(deps, feeder.to_string())
+        }
+        ExecuteMsg::UpdateConfig {
+            quorum,
+            max_submission_age_seconds,
+            max_deviation,
+            min_update_interval_seconds,
+        } => {
+            let mut config = CONFIG.load(deps.storage)?;
+            config.quorum = quorum.unwrap_or(config.quorum);
+            config.max_submission_age_seconds = max_submission_age_seconds
+                .unwrap_or(config.max_submission_age_seconds);
+            config.max_deviation =
+                max_deviation.unwrap_or(config.max_deviation);
+            config.min_update_interval_seconds = min_update_interval_seconds
+                .unwrap_or(config.min_update_interval_seconds);
+            execute_update_config(deps, config)
+        }
+        ExecuteMsg::ConfirmPrice { ticker, feeder } => {
+            execute_confirm_price(deps, env, info, is_admin, ticker, feeder)
+        }
+        ExecuteMsg::SetPublisherKey { feeder, key } => {
+            execute_set_publisher_key(deps, feeder.to_string(), key)
+        }
+        ExecuteMsg::RemovePublisherKey { feeder } => {
+            let feeder = deps.api.addr_validate(feeder.as_str())?;
+            PUBLISHER_KEYS.remove(deps.storage, &feeder);
+            Ok(Response::new())
+        }
+        ExecuteMsg::SubmitSigned { payload, signature } => {
+            execute_submit_signed(deps, env, payload, signature)
+        }
+        ExecuteMsg::SetFeedInfo { ticker, info } => {
+            execute_set_feed_info(deps, ticker, info)
+        }
+        ExecuteMsg::AddSubscriber {
+            contract,
+            tickers,
+            gas_limit,
+        } => execute_add_subscriber(
+            deps,
+            contract.to_string(),
+            tickers,
+            gas_limit,
+        ),
+        ExecuteMsg::RemoveSubscriber { contract } => {
+            execute_remove_subscriber(deps, contract.to_string())
+        }
+    }
+}
+
+fn execute_add_subscriber(
+    deps: DepsMut,
+    contract: String,
+    tickers: Vec<String>,
+    gas_limit: Option<u64>,
+) -> Result<Response, ContractError> {
+    let contract = deps.api.addr_validate(&contract)?;
+    if find_subscriber(deps.storage, &contract)?.is_some() {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "{} is already subscribed",
+            contract
+        ))));
+    }
+
+    let id = NEXT_SUBSCRIBER_ID
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    SUBSCRIBERS.save(
+        deps.storage,
+        id,
+        &Subscriber {
+            contract,
+            tickers,
+            gas_limit,
+        },
+    )?;
+    NEXT_SUBSCRIBER_ID.save(deps.storage, &(id + 1))?;
+
+    Ok(Response::new())
+}
+
+fn execute_remove_subscriber(
+    deps: DepsMut,
+    contract: String,
+) -> Result<Response, ContractError> {
+    let contract = deps.api.addr_validate(&contract)?;
+    let id = find_subscriber(deps.storage, &contract)?.ok_or_else(|| {
+        StdError::generic_err(format!("{} is not subscribed", contract))
+    })?;
+
+    SUBSCRIBERS.remove(deps.storage, id);
+
+    Ok(Response::new())
+}
+
+fn find_subscriber(
+    storage: &dyn Storage,
+    contract: &Addr,
+) -> StdResult<Option<u64>> {
+    for item in SUBSCRIBERS.range(storage, None, None, Order::Ascending) {
+        let (id, subscriber) = item?;
+        if &subscriber.contract == contract {
+            return Ok(Some(id));
+        }
+    }
+
+    Ok(None)
+}
+
+/*
+ * One callback per interested subscriber, a failing one is reported by
+ * reply instead of reverting the update
+ */
+fn notify_subscribers(
+    storage: &dyn Storage,
+    ticker: &str,
+    entry: &PriceEntry,
+) -> StdResult<Vec<SubMsg>> {
+    let callback = to_binary(&CallbackMsg::OraclePriceUpdate {
+        ticker: ticker.to_string(),
+        price: entry.price,
+        expo: entry.expo,
+        timestamp: entry.updated_at,
+    })?;
+
+    let mut callbacks = vec![];
+    for item in SUBSCRIBERS.range(storage, None, None, Order::Ascending) {
+        let (id, subscriber) = item?;
+        if !subscriber.tickers.is_empty()
+            && !subscriber.tickers.iter().any(|t| t == ticker)
+        {
+            continue;
+        }
+
+        callbacks.push(
+            SubMsg::reply_on_error(
+                WasmMsg::Execute {
+                    contract_addr: subscriber.contract.to_string(),
+                    msg: callback.clone(),
+                    funds: vec![],
+                },
+                id,
+            )
+            .with_gas_limit(
+                subscriber.gas_limit.unwrap_or(DEFAULT_CALLBACK_GAS_LIMIT),
+            ),
+        );
+    }
+
+    Ok(callbacks)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(
+    deps: DepsMut,
+    _env: Env,
+    msg: Reply,
+) -> Result<Response, ContractError> {
+    match msg.result {
+        SubMsgResult::Ok(_) => Ok(Response::new()),
+        SubMsgResult::Err(error) => {
+            /* The subscriber may have been removed since */
+            let contract = SUBSCRIBERS
+                .may_load(deps.storage, msg.id)?
+                .map(|subscriber| subscriber.contract.to_string())
+                .unwrap_or_default();
+
+            Ok(Response::new().add_event(
+                Event::new("forecast_deliverdao").add_attributes(vec![
+                    ("callback_failed", contract),
+                    ("error", error),
+                ]),
+            ))
+        }
+    }
+}
+
+fn execute_set_feed_info(
+    deps: DepsMut,
+    ticker: String,
+    info: FeedInfo,
+) -> Result<Response, ContractError> {
+    if ticker.is_empty() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "The ticker cannot be empty",
+        )));
+    }
+    /* Recorded prices and cumulatives are in the old exponent */
+    let has_history = PRICE_HISTORY
+        .prefix(&ticker)
+        .keys_raw(deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_some();
+    if has_history && info.expo != feed_expo(deps.storage, &ticker)? {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "The exponent of {} cannot change once it has prices",
+            ticker
+        ))));
+    }
+
+    FEEDS.save(deps.storage, &ticker, &info)?;
+
+    Ok(Response::new())
+}
+
+fn feed_expo(storage: &dyn Storage, ticker: &str) -> StdResult<i32> {
+    Ok(FEEDS
+        .may_load(storage, ticker)?
+        .map(|info| info.expo)
+        .unwrap_or_default())
+}
+
+fn execute_set_publisher_key(
+    deps: DepsMut,
+    feeder: String,
+    key: PublisherKey,
+) -> Result<Response, ContractError> {
+    let feeder = deps.api.addr_validate(&feeder)?;
+    if !FEEDERS.has(deps.storage, &feeder) {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "{} is not a feeder",
+            feeder
+        ))));
+    }
+
+    PUBLISHER_KEYS.save(deps.storage, &feeder, &key)?;
+
+    Ok(Response::new())
+}
+
+fn execute_submit_signed(
+    deps: DepsMut,
+    env: Env,
+    payload: Binary,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    let price_payload: PricePayload = from_slice(&payload)?;
+    let publisher = deps.api.addr_validate(price_payload.publisher.as_str())?;
+    if price_payload.chain_id != env.block.chain_id
+        || price_payload.contract != env.contract.address
+    {
+        return Err(ContractError::Std(StdError::generic_err(
+            "The payload is meant for another oracle",
+        )));
+    }
+
+    let key = match PUBLISHER_KEYS.may_load(deps.storage, &publisher)? {
+        Some(key) if FEEDERS.has(deps.storage, &publisher) => key,
+        _ => return Err(ContractError::Unauthorized {}),
+    };
+
+    let verified = match key.scheme {
+        KeyScheme::Secp256k1 => deps.api.secp256k1_verify(
+            Sha256::digest(payload.as_slice()).as_slice(),
+            &signature,
+            &key.pubkey,
+        ),
+        KeyScheme::Ed25519 => {
+            deps.api.ed25519_verify(&payload, &signature, &key.pubkey)
         }
     }
+    .map_err(StdError::from)?;
+    if !verified {
+        return Err(ContractError::Std(StdError::generic_err(
+            "Invalid payload signature",
+        )));
+    }
+
+    /* Each payload is accepted once and in order */
+    let ticker = price_payload.ticker;
+    let timestamp = price_payload.timestamp;
+    if let Some(last) =
+        LAST_SIGNED.may_load(deps.storage, (&publisher, &ticker))?
+    {
+        if timestamp <= last {
+            return Err(ContractError::Std(StdError::generic_err(
+                "The payload is older than the last one submitted",
+            )));
+        }
+    }
+    if timestamp > env.block.time {
+        return Err(ContractError::Std(StdError::generic_err(
+            "The payload is from the future",
+        )));
+    }
+    /* Without a maximum age a stale payload could be relayed any time */
+    let config = CONFIG.load(deps.storage)?;
+    let max_age = config.max_submission_age_seconds.u128() as u64;
+    if max_age == 0 {
+        return Err(ContractError::Std(StdError::generic_err(
+            "Signed payloads need a maximum submission age",
+        )));
+    }
+    if timestamp.plus_seconds(max_age) < env.block.time {
+        return Err(ContractError::Std(StdError::generic_err(
+            "The payload is too old",
+        )));
+    }
+    LAST_SIGNED.save(deps.storage, (&publisher, &ticker), &timestamp)?;
+
+    execute_set_price(
+        deps,
+        env,
+        publisher,
+        ticker,
+        price_payload.price,
+        timestamp,
+    )
+}
+
+fn execute_add_feeder(
+    deps: DepsMut,
+    feeder: String,
+) -> Result<Response, ContractError> {
+    let feeder = deps.api.addr_validate(&feeder)?;
+    if FEEDERS.has(deps.storage, &feeder) {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "{} is already a feeder",
+            feeder
+        ))));
+    }
+
+    FEEDERS.save(deps.storage, &feeder, &Empty {})?;
+
+    Ok(Response::new())
+}
+
+fn execute_remove_feeder(
+    deps: DepsMut,
+    feeder: String,
+) -> Result<Response, ContractError> {
+    let feeder = deps.api.addr_validate(&feeder)?;
+    if !FEEDERS.has(deps.storage, &feeder) {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "{} is not a feeder",
+            feeder
+        ))));
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    if feeder_count(deps.storage) <= config.quorum as usize {
+        return Err(ContractError::Std(StdError::generic_err(
+            "Removing the feeder would make the quorum unreachable",
+        )));
+    }
+
+    FEEDERS.remove(deps.storage, &feeder);
+    PUBLISHER_KEYS.remove(deps.storage, &feeder);
+    /* Its submissions must not count towards the median anymore */
+    let tickers = SUBMISSIONS
+        .prefix(&feeder)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<String>>>()?;
+    for ticker in tickers {
+        SUBMISSIONS.remove(deps.storage, (&feeder, &ticker));
+    }
+    /* Nor can its held back prices be confirmed */
+    let pending = PENDING_PRICES
+        .keys(deps.storage, None, None, Order::Ascending)
+        .filter(|key| {
+            key.as_ref()
+                .map(|(_, addr)| *addr == feeder)
+                .unwrap_or(true)
+        })
+        .collect::<StdResult<Vec<(String, Addr)>>>()?;
+    for (ticker, addr) in pending {
+        PENDING_PRICES.remove(deps.storage, (&ticker, &addr));
+    }
+    let signed = LAST_SIGNED
+        .prefix(&feeder)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<String>>>()?;
+    for ticker in signed {
+        LAST_SIGNED.remove(deps.storage, (&feeder, &ticker));
+    }
+
+    Ok(Response::new())
 }
 
-fn execute_set_owner(
+fn execute_update_config(
+    deps: DepsMut,
+    config: Config,
+) -> Result<Response, ContractError> {
+    if config.quorum == 0 {
+        return Err(ContractError::Std(StdError::generic_err(
+            "The quorum must be at least 1",
+        )));
+    }
+    if config.quorum as usize > feeder_count(deps.storage) {
+        return Err(ContractError::Std(StdError::generic_err(
+            "The quorum cannot exceed the number of feeders",
+        )));
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new())
+}
+
+fn feeder_count(storage: &dyn Storage) -> usize {
+    FEEDERS
+        .keys_raw(storage, None, None, Order::Ascending)
+        .count()
+}
+
+fn execute_propose_owner(
     deps: DepsMut,
     owner: String,
 ) -> Result<Response, ContractError> {
     let owner = deps.api.addr_validate(&owner)?;
-    ADMIN.save(deps.storage, &owner)?;
+    PENDING_OWNER.save(deps.storage, &owner)?;
 
-    Ok(Response::new())
+    Ok(Response::new().add_event(
+        Event::new("forecast_deliverdao")
+            .add_attribute("owner_proposed", owner.to_string()),
+    ))
 }
 
+fn execute_accept_ownership(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let previous_owner = ADMIN.load(deps.storage)?;
+    ADMIN.save(deps.storage, &info.sender)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new().add_event(
+        Event::new("forecast_deliverdao").add_attributes(vec![
+            ("owner_changed", info.sender.to_string()),
+            ("previous_owner", previous_owner.to_string()),
+        ]),
+    ))
+}
+
+/* `time` is when the price was observed, the block time unless signed */
 fn execute_set_price(
     deps: DepsMut,
+    env: Env,
+    feeder: Addr,
+    ticker: String,
     price: Uint128,
+    time: Timestamp,
 ) -> Result<Response, ContractError> {
-    PRICE.save(deps.storage, &price.u128())?;
+    if ticker.is_empty() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "The ticker cannot be empty",
+        )));
+    }
+    if price.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "The price cannot be zero",
+        )));
+    }
 
-    Ok(Response::new())
+    let config = CONFIG.load(deps.storage)?;
+    let interval = config.min_update_interval_seconds.u128() as u64;
+    /* A held back update counts as one too */
+    let last_update = SUBMISSIONS
+        .may_load(deps.storage, (&feeder, &ticker))?
+        .map(|last| last.updated_at)
+        .max(
+            PENDING_PRICES
+                .may_load(deps.storage, (&ticker, &feeder))?
+                .map(|pending| pending.submitted_at),
+        );
+    if let Some(last) = last_update {
+        if last.plus_seconds(interval) > env.block.time {
+            return Err(ContractError::Std(StdError::generic_err(format!(
+                "Updates of {} are limited to one every {} second(s)",
+                ticker, interval
+            ))));
+        }
+    }
+
+    /* A large move waits for a second opinion */
+    if let Some(previous) = PRICES.may_load(deps.storage, &ticker)? {
+        if is_deviating(&config, previous.price, price) {
+            /* Another feeder already holding the same price is one */
+            let matching = PENDING_PRICES
+                .prefix(&ticker)
+                .range(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<(Addr, PendingPrice)>>>()?
+                .into_iter()
+                .find(|(other, pending)| {
+                    *other != feeder
+                        && pending.price == price
+                        && FEEDERS.has(deps.storage, other)
+                });
+            if let Some((other, pending)) = matching {
+                PENDING_PRICES.remove(deps.storage, (&ticker, &other));
+                PENDING_PRICES.remove(deps.storage, (&ticker, &feeder));
+                record_submission(
+                    deps.storage,
+                    &env,
+                    &other,
+                    &ticker,
+                    price,
+                    pending.submitted_at,
+                )?;
+                let response = publish_price(
+                    deps.storage,
+                    &env,
+                    &feeder,
+                    &ticker,
+                    price,
+                    time,
+                )?;
+
+                return Ok(response.add_event(
+                    Event::new("forecast_deliverdao").add_attributes(vec![
+                        ("price_confirmed", ticker),
+                        ("price", price.to_string()),
+                        ("feeder", other.to_string()),
+                        ("confirmed_by", feeder.to_string()),
+                    ]),
+                ));
+            }
+
+            PENDING_PRICES.save(
+                deps.storage,
+                (&ticker, &feeder),
+                &PendingPrice {
+                    price,
+                    previous_price: previous.price,
+                    feeder: feeder.clone(),
+                    submitted_at: time,
+                },
+            )?;
+
+            return Ok(Response::new().add_event(
+                Event::new("forecast_deliverdao").add_attributes(vec![
+                    ("price_pending", ticker),
+                    ("price", price.to_string()),
+                    ("previous_price", previous.price.to_string()),
+                    ("feeder", feeder.to_string()),
+                ]),
+            ));
+        }
+    }
+
+    /* A newer price from the same feeder supersedes its pending one */
+    PENDING_PRICES.remove(deps.storage, (&ticker, &feeder));
+
+    Ok(publish_price(
+        deps.storage,
+        &env,
+        &feeder,
+        &ticker,
+        price,
+        time,
+    )?)
+}
+
+/*
+ * Submit a price, with an event for the submission and, once quorum is met,
+ * one for the published price along with the subscriber callbacks
+ */
+fn publish_price(
+    storage: &mut dyn Storage,
+    env: &Env,
+    feeder: &Addr,
+    ticker: &str,
+    price: Uint128,
+    time: Timestamp,
+) -> StdResult<Response> {
+    let mut response = Response::new().add_event(
+        Event::new("forecast_deliverdao").add_attributes(vec![
+            ("price_submitted", ticker.to_string()),
+            ("price", price.to_string()),
+            ("feeder", feeder.to_string()),
+        ]),
+    );
+
+    if let Some(entry) =
+        submit_price(storage, env, feeder, ticker, price, time)?
+    {
+        response = response
+            .add_submessages(notify_subscribers(storage, ticker, &entry)?)
+            .add_event(Event::new("forecast_deliverdao").add_attributes(vec![
+                ("price_updated", ticker.to_string()),
+                ("price", entry.price.to_string()),
+                ("expo", entry.expo.to_string()),
+                ("timestamp", entry.updated_at.nanos().to_string()),
+                ("height", entry.height.to_string()),
+            ]));
+    }
+
+    Ok(response)
+}
+
+fn is_deviating(config: &Config, previous: Uint128, price: Uint128) -> bool {
+    if config.max_deviation.is_zero() || previous.is_zero() {
+        return false;
+    }
+
+    let change = if price > previous {
+        price - previous
+    } else {
+        previous - price
+    };
+    Decimal::from_ratio(change, previous) > config.max_deviation
+}
+
+fn execute_confirm_price(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    is_admin: bool,
+    ticker: String,
+    feeder: Addr,
+) -> Result<Response, ContractError> {
+    let pending = PENDING_PRICES
+        .may_load(deps.storage, (&ticker, &feeder))?
+        .ok_or_else(|| {
+            StdError::generic_err(format!(
+                "No pending price for {} from {}",
+                ticker, feeder
+            ))
+        })?;
+
+    if pending.feeder == info.sender && !is_admin {
+        return Err(ContractError::Std(StdError::generic_err(
+            "A second feeder has to confirm the price",
+        )));
+    }
+    if !FEEDERS.has(deps.storage, &pending.feeder) {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "{} is no longer a feeder",
+            pending.feeder
+        ))));
+    }
+
+    PENDING_PRICES.remove(deps.storage, (&ticker, &feeder));
+    let response = publish_price(
+        deps.storage,
+        &env,
+        &pending.feeder,
+        &ticker,
+        pending.price,
+        pending.submitted_at,
+    )?;
+
+    Ok(
+        response.add_event(Event::new("forecast_deliverdao").add_attributes(
+            vec![
+                ("price_confirmed", ticker),
+                ("price", pending.price.to_string()),
+                ("feeder", pending.feeder.to_string()),
+                ("confirmed_by", info.sender.to_string()),
+            ],
+        )),
+    )
+}
+
+/* Store a feeder's submission and publish the median once quorum is met */
+fn submit_price(
+    storage: &mut dyn Storage,
+    env: &Env,
+    feeder: &Addr,
+    ticker: &str,
+    price: Uint128,
+    time: Timestamp,
+) -> StdResult<Option<PriceEntry>> {
+    let submission =
+        record_submission(storage, env, feeder, ticker, price, time)?;
+
+    let config = CONFIG.load(storage)?;
+    let prices = fresh_prices(storage, &config, ticker, env.block.time)?;
+    if prices.len() < config.quorum as usize {
+        return Ok(None);
+    }
+
+    /*
+     * Published when the block runs, a signed timestamp only decides
+     * freshness and replays; relayers must not pick which past entry to
+     * rewrite
+     */
+    let entry = PriceEntry {
+        price: median(&prices),
+        updated_at: env.block.time,
+        ..submission
+    };
+    record_price(storage, ticker, &entry)?;
+
+    Ok(Some(entry))
+}
+
+/* A feeder's latest submission, counted by the next median */
+fn record_submission(
+    storage: &mut dyn Storage,
+    env: &Env,
+    feeder: &Addr,
+    ticker: &str,
+    price: Uint128,
+    time: Timestamp,
+) -> StdResult<PriceEntry> {
+    let submission = PriceEntry {
+        price,
+        updated_at: time,
+        height: env.block.height,
+        triggered_by: feeder.clone(),
+        expo: feed_expo(storage, ticker)?,
+    };
+    SUBMISSIONS.save(storage, (feeder, ticker), &submission)?;
+
+    Ok(submission)
+}
+
+/*
+ * Sorted submissions of the current feeders still recent enough at `now`.
+ * Zero is never a price, older oracles seeded it at instantiation.
+ */
+fn fresh_prices(
+    storage: &dyn Storage,
+    config: &Config,
+    ticker: &str,
+    now: Timestamp,
+) -> StdResult<Vec<Uint128>> {
+    let feeders = FEEDERS
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<Addr>>>()?;
+
+    let mut prices = vec![];
+    for feeder in feeders {
+        if let Some(submission) =
+            SUBMISSIONS.may_load(storage, (&feeder, ticker))?
+        {
+            let max_age = config.max_submission_age_seconds.u128() as u64;
+            if !submission.price.is_zero()
+                && (max_age == 0
+                    || submission.updated_at.plus_seconds(max_age) >= now)
+            {
+                prices.push(submission.price);
+            }
+        }
+    }
+    prices.sort();
+
+    Ok(prices)
+}
+
+/* Middle price, the mean of the two middle ones for an even count */
+fn median(sorted_prices: &[Uint128]) -> Uint128 {
+    let mid = sorted_prices.len() / 2;
+    if sorted_prices.len() % 2 == 1 {
+        return sorted_prices[mid];
+    }
+
+    let (low, high) = (sorted_prices[mid - 1], sorted_prices[mid]);
+    low + (high - low).multiply_ratio(1u128, 2u128)
+}
+
+/* Latest price and its history entry, a later update in the same block wins */
+fn record_price(
+    storage: &mut dyn Storage,
+    ticker: &str,
+    entry: &PriceEntry,
+) -> StdResult<()> {
+    /* The previous price held from its own update until this one */
+    let cumulative = match PRICES.may_load(storage, ticker)? {
+        Some(last) => {
+            let key = (ticker, last.updated_at.nanos());
+            let elapsed = entry
+                .updated_at
+                .seconds()
+                .saturating_sub(last.updated_at.seconds());
+            PRICE_CUMULATIVES
+                .may_load(storage, key)?
+                .unwrap_or_default()
+                + Uint256::from(last.price) * Uint256::from(elapsed as u128)
+        }
+        None => Uint256::zero(),
+    };
+
+    let key = (ticker, entry.updated_at.nanos());
+    PRICES.save(storage, ticker, entry)?;
+    PRICE_CUMULATIVES.save(storage, key, &cumulative)?;
+    PRICE_HISTORY.save(storage, key, entry)
+}
+
+/* Last history entry at or before `time` with its key */
+fn last_entry_at(
+    storage: &dyn Storage,
+    ticker: &str,
+    time: Timestamp,
+) -> StdResult<Option<(u64, PriceEntry)>> {
+    PRICE_HISTORY
+        .prefix(ticker)
+        .range(
+            storage,
+            None,
+            Some(Bound::inclusive(time.nanos())),
+            Order::Descending,
+        )
+        .next()
+        .transpose()
+}
+
+/*
+ * Cumulative price at `time`, extended from the last recorded one. None
+ * before the ticker's first price
+ */
+fn cumulative_at(
+    storage: &dyn Storage,
+    ticker: &str,
+    time: Timestamp,
+) -> StdResult<Option<Uint256>> {
+    let (key, entry) = match last_entry_at(storage, ticker, time)? {
+        Some(last) => last,
+        None => return Ok(None),
+    };
+    let cumulative = PRICE_CUMULATIVES
+        .may_load(storage, (ticker, key))?
+        .ok_or_else(|| {
+            StdError::generic_err(format!(
+                "No cumulative price for {} at {}",
+                ticker, time
+            ))
+        })?;
+    let elapsed = time.seconds().saturating_sub(entry.updated_at.seconds());
+
+    Ok(Some(
+        cumulative
+            + Uint256::from(entry.price) * Uint256::from(elapsed as u128),
+    ))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::Price {} => to_binary(&query_price(deps, env)?),
+        QueryMsg::Price { ticker } => {
+            to_binary(&query_price(deps, env, ticker)?)
+        }
+        QueryMsg::AllPrices { start_after, limit } => {
+            to_binary(&query_all_prices(deps, start_after, limit)?)
+        }
+        QueryMsg::PriceAt { ticker, time } => {
+            to_binary(&query_price_at(deps, ticker, time)?)
+        }
+        QueryMsg::PriceHistory {
+            ticker,
+            start_after,
+            limit,
+        } => to_binary(&query_price_history(deps, ticker, start_after, limit)?),
+        QueryMsg::Twap { ticker, start, end } => {
+            to_binary(&query_twap(deps, ticker, start, end)?)
+        }
+        QueryMsg::Candles {
+            ticker,
+            interval,
+            start_after,
+            limit,
+        } => to_binary(&query_candles(
+            deps,
+            ticker,
+            interval,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Owner {} => to_binary(&query_owner(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps)?),
+        QueryMsg::Feeders {} => to_binary(&query_feeders(deps)?),
+        QueryMsg::PendingPrice { ticker } => {
+            to_binary(&query_pending_price(deps, ticker)?)
+        }
+        QueryMsg::PublisherKey { feeder } => {
+            to_binary(&query_publisher_key(deps, feeder.to_string())?)
+        }
+        QueryMsg::FeedInfo { ticker } => {
+            to_binary(&query_feed_info(deps, ticker)?)
+        }
+        QueryMsg::Subscribers {} => to_binary(&query_subscribers(deps)?),
+    }
+}
+
+/* Median of the fresh submissions, an error below quorum */
+fn query_price(
+    deps: Deps,
+    env: Env,
+    ticker: String,
+) -> StdResult<PriceResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let prices = fresh_prices(deps.storage, &config, &ticker, env.block.time)?;
+
+    if prices.is_empty() {
+        return Err(StdError::generic_err(format!("No price for {}", ticker)));
     }
+    if prices.len() < config.quorum as usize {
+        return Err(StdError::generic_err(format!(
+            "Only {} of {} feeders reported {}",
+            prices.len(),
+            config.quorum,
+            ticker
+        )));
+    }
+
+    let timestamp = PRICES
+        .may_load(deps.storage, &ticker)?
+        .map(|entry| entry.updated_at)
+        .unwrap_or(env.block.time);
+
+    Ok(PriceResponse {
+        value: median(&prices),
+        expo: feed_expo(deps.storage, &ticker)?,
+        timestamp,
+    })
+}
+
+fn query_feed_info(deps: Deps, ticker: String) -> StdResult<FeedInfoResponse> {
+    FEEDS.may_load(deps.storage, &ticker)?.ok_or_else(|| {
+        StdError::generic_err(format!("No feed info for {}", ticker))
+    })
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    CONFIG.load(deps.storage)
+}
+
+fn query_owner(deps: Deps) -> StdResult<OwnerResponse> {
+    Ok(OwnerResponse {
+        owner: ADMIN.load(deps.storage)?,
+    })
+}
+
+fn query_pending_owner(deps: Deps) -> StdResult<PendingOwnerResponse> {
+    Ok(PendingOwnerResponse {
+        pending_owner: PENDING_OWNER.may_load(deps.storage)?,
+    })
+}
+
+fn query_feeders(deps: Deps) -> StdResult<FeedersResponse> {
+    let feeders = FEEDERS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+
+    Ok(FeedersResponse { feeders })
+}
+
+fn query_all_prices(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllPricesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let prices = PRICES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (ticker, entry) = item?;
+            Ok(TickerPrice {
+                ticker,
+                price: entry.price,
+                expo: entry.expo,
+                updated_at: entry.updated_at,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(AllPricesResponse { prices })
+}
+
+fn query_price_at(
+    deps: Deps,
+    ticker: String,
+    time: Timestamp,
+) -> StdResult<PriceAtResponse> {
+    let entry =
+        last_entry_at(deps.storage, &ticker, time)?.map(|(_, entry)| entry);
+
+    Ok(PriceAtResponse { entry })
+}
+
+fn query_twap(
+    deps: Deps,
+    ticker: String,
+    start: Timestamp,
+    end: Timestamp,
+) -> StdResult<TwapResponse> {
+    let seconds = end.seconds().saturating_sub(start.seconds());
+    if seconds == 0 {
+        return Err(StdError::generic_err(
+            "The TWAP window must end at least one second after it starts",
+        ));
+    }
+
+    /* A price at the start means there is one at the end too */
+    let twap = match (
+        cumulative_at(deps.storage, &ticker, start)?,
+        cumulative_at(deps.storage, &ticker, end)?,
+    ) {
+        (Some(from), Some(to)) => Some(
+            Uint128::try_from((to - from) / Uint256::from(seconds as u128))
+                .map_err(|e| StdError::generic_err(e.to_string()))?,
+        ),
+        _ => None,
+    };
+
+    Ok(TwapResponse {
+        expo: feed_expo(deps.storage, &ticker)?,
+        ticker,
+        twap,
+        start,
+        end,
+    })
+}
+
+fn query_candles(
+    deps: Deps,
+    ticker: String,
+    interval: u64,
+    start_after: Option<Timestamp>,
+    limit: Option<u32>,
+) -> StdResult<CandlesResponse> {
+    if interval == 0 {
+        return Err(StdError::generic_err("Candle interval must be positive"));
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    /* Skip the whole candle `start_after` falls into */
+    let start = start_after.map(|time| {
+        let next = (time.seconds() / interval + 1) * interval;
+        Bound::inclusive(Timestamp::from_seconds(next).nanos())
+    });
+
+    let mut candles: Vec<Candle> = vec![];
+    for item in PRICE_HISTORY.prefix(&ticker).range(
+        deps.storage,
+        start,
+        None,
+        Order::Ascending,
+    ) {
+        let (_, entry) = item?;
+        let open_time = Timestamp::from_seconds(
+            entry.updated_at.seconds() / interval * interval,
+        );
+
+        match candles.last_mut() {
+            Some(candle) if candle.open_time == open_time => {
+                candle.high = candle.high.max(entry.price);
+                candle.low = candle.low.min(entry.price);
+                candle.close = entry.price;
+            }
+            _ => {
+                if candles.len() == limit {
+                    break;
+                }
+                candles.push(Candle {
+                    open_time,
+                    open: entry.price,
+                    high: entry.price,
+                    low: entry.price,
+                    close: entry.price,
+                });
+            }
+        }
+    }
+
+    Ok(CandlesResponse {
+        ticker,
+        interval,
+        candles,
+    })
+}
+
+fn query_price_history(
+    deps: Deps,
+    ticker: String,
+    start_after: Option<Timestamp>,
+    limit: Option<u32>,
+) -> StdResult<PriceHistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|time| Bound::exclusive(time.nanos()));
+
+    let entries = PRICE_HISTORY
+        .prefix(&ticker)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, entry)| entry))
+        .collect::<StdResult<_>>()?;
+
+    Ok(PriceHistoryResponse { ticker, entries })
+}
+
+fn query_pending_price(
+    deps: Deps,
+    ticker: String,
+) -> StdResult<PendingPriceResponse> {
+    let pending = PENDING_PRICES
+        .prefix(&ticker)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, pending)| pending))
+        .collect::<StdResult<_>>()?;
+
+    Ok(PendingPriceResponse { pending })
+}
+
+fn query_publisher_key(
+    deps: Deps,
+    feeder: String,
+) -> StdResult<PublisherKeyResponse> {
+    let feeder = deps.api.addr_validate(&feeder)?;
+    let key = PUBLISHER_KEYS.may_load(deps.storage, &feeder)?;
+
+    Ok(PublisherKeyResponse { key })
 }
 
-fn query_price(deps: Deps, _env: Env) -> StdResult<Uint128> {
-    let price: Uint128 = PRICE.load(deps.storage)?.into();
+fn query_subscribers(deps: Deps) -> StdResult<SubscribersResponse> {
+    let subscribers = SUBSCRIBERS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, subscriber)| subscriber))
+        .collect::<StdResult<_>>()?;
 
-    Ok(price)
+    Ok(SubscribersResponse { subscribers })
 }
@@ -1,5 +1,39 @@
-use cosmwasm_std::Addr;
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Empty, Timestamp, Uint256};
+use cw_storage_plus::{Item, Map};
+use forecast_deliverdao::fast_oracle::{
+    Config, FeedInfo, PendingPrice, PriceEntry, PublisherKey, Subscriber,
+};
 
 pub const ADMIN: Item<Addr> = Item::new("owner");
-pub const PRICE: Item<u128> = Item::new("price");
+/* Proposed owner until it accepts */
+pub const PENDING_OWNER: Item<Addr> = Item::new("pending_owner");
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const FEEDERS: Map<&Addr, Empty> = Map::new("feeders");
+/* Key a feeder signs relayed payloads with */
+pub const PUBLISHER_KEYS: Map<&Addr, PublisherKey> = Map::new("publisher_keys");
+/* Timestamp of the last signed payload per feeder and ticker */
+pub const LAST_SIGNED: Map<(&Addr, &str), Timestamp> = Map::new("last_signed");
+/* Last submission of each feeder per ticker */
+pub const SUBMISSIONS: Map<(&Addr, &str), PriceEntry> = Map::new("submissions");
+/* Keyed by the reply id of their callbacks */
+pub const SUBSCRIBERS: Map<u64, Subscriber> = Map::new("subscribers");
+pub const NEXT_SUBSCRIBER_ID: Item<u64> = Item::new("next_subscriber_id");
+/* Feeds without an entry have an exponent of 0 */
+pub const FEEDS: Map<&str, FeedInfo> = Map::new("feeds");
+/* At most one update per ticker and feeder waiting for a confirmation */
+pub const PENDING_PRICES: Map<(&str, &Addr), PendingPrice> =
+    Map::new("pending_prices");
+/* Latest published (median) price per ticker */
+pub const PRICES: Map<&str, PriceEntry> = Map::new("prices");
+/*
+ * Published medians per ticker, keyed by block time in nanos, so only the
+ * last one of a block is kept
+ */
+pub const PRICE_HISTORY: Map<(&str, u64), PriceEntry> =
+    Map::new("price_history");
+/* Sum of price * seconds since the first recorded price, keyed like history */
+pub const PRICE_CUMULATIVES: Map<(&str, u64), Uint256> =
+    Map::new("price_cumulatives");
+
+/* Single price of pre-ticker oracles, only read by migrate */
+pub const LEGACY_PRICE: Item<u128> = Item::new("price");
@@ -1,19 +1,43 @@
 use std::borrow::BorrowMut;
 
-use cosmwasm_std::{to_binary, Addr, CosmosMsg, Empty, Uint128, WasmMsg};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Attribute, Binary, CosmosMsg, Decimal, Empty,
+    Reply, ReplyOn, StdError, StdResult, SubMsgResult, Timestamp, Uint128,
+    WasmMsg,
+};
 use cw_multi_test::{App, Contract, ContractWrapper, Executor};
-use forecast_deliverdao::fast_oracle::msg::InstantiateMsg;
+use forecast_deliverdao::fast_oracle::msg::{
+    CallbackMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+};
+use forecast_deliverdao::fast_oracle::response::{
+    AllPricesResponse, Candle, CandlesResponse, FeedInfoResponse,
+    FeedersResponse, OwnerResponse, PendingOwnerResponse, PendingPriceResponse,
+    PriceAtResponse, PriceHistoryResponse, PriceResponse, SubscribersResponse,
+    TwapResponse,
+};
+use forecast_deliverdao::fast_oracle::{
+    FeedInfo, KeyScheme, PriceEntry, PublisherKey, DEFAULT_TICKER,
+};
+
+use crate::error::ContractError;
+use crate::state::{ADMIN, LEGACY_PRICE, PRICES, SUBMISSIONS};
 
 fn mock_app() -> App {
     App::default()
 }
 
+fn generic_err(msg: &str) -> ContractError {
+    ContractError::Std(StdError::generic_err(msg))
+}
+
 pub fn contract_fast_oracle() -> Box<dyn Contract<Empty>> {
     let contract = ContractWrapper::new(
         crate::contract::execute,
         crate::contract::instantiate,
         crate::contract::query,
-    );
+    )
+    .with_reply(crate::contract::reply);
     Box::new(contract)
 }
 
@@ -41,18 +65,20 @@ fn proper_initialization() {
 
     let oracle_addr = create_fast_oracle(router.borrow_mut(), &owner);
 
-    let price: Uint128 = router
+    /* Nothing is published until a feeder reports */
+    router
         .wrap()
-        .query_wasm_smart(
+        .query_wasm_smart::<PriceResponse>(
             &oracle_addr,
-            &forecast_deliverdao::fast_oracle::msg::QueryMsg::Price {},
+            &forecast_deliverdao::fast_oracle::msg::QueryMsg::Price {
+                ticker: DEFAULT_TICKER.to_string(),
+            },
         )
-        .unwrap();
+        .unwrap_err();
 
     let info = router.wrap().query_wasm_contract_info(oracle_addr).unwrap();
 
     assert_eq!(info.admin, Some(owner.to_string()));
-    assert_eq!(price, Uint128::zero());
 }
 
 #[test]
@@ -66,6 +92,7 @@ fn proper_update_price() {
         contract_addr: oracle_addr.to_string(),
         msg: to_binary(
             &forecast_deliverdao::fast_oracle::msg::ExecuteMsg::Update {
+                ticker: DEFAULT_TICKER.to_string(),
                 price: new_price.clone(),
             },
         )
@@ -77,15 +104,17 @@ fn proper_update_price() {
         .execute_multi(owner, [update_price_msg].to_vec())
         .unwrap();
 
-    let price: Uint128 = router
+    let price: PriceResponse = router
         .wrap()
         .query_wasm_smart(
             &oracle_addr,
-            &forecast_deliverdao::fast_oracle::msg::QueryMsg::Price {},
+            &forecast_deliverdao::fast_oracle::msg::QueryMsg::Price {
+                ticker: DEFAULT_TICKER.to_string(),
+            },
         )
         .unwrap();
 
-    assert_eq!(price, new_price);
+    assert_eq!(price.value, new_price);
 }
 
 #[test]
@@ -101,6 +130,7 @@ fn proper_deny_random_user_update() {
         contract_addr: oracle_addr.to_string(),
         msg: to_binary(
             &forecast_deliverdao::fast_oracle::msg::ExecuteMsg::Update {
+                ticker: DEFAULT_TICKER.to_string(),
                 price: new_price.clone(),
             },
         )
@@ -112,3 +142,1424 @@ fn proper_deny_random_user_update() {
         .execute_multi(faker, [update_price_msg].to_vec())
         .expect_err("Faker should fail");
 }
+
+#[test]
+fn proper_migrate() {
+    let mut deps = mock_dependencies();
+
+    /* Oracles instantiated before cw2 have no version */
+    crate::contract::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    let version = cw2::get_contract_version(&deps.storage).unwrap();
+    assert_eq!(version.contract, "deliverdao:fast_oracle");
+
+    cw2::set_contract_version(
+        deps.as_mut().storage,
+        "deliverdao:fast_oracle",
+        "99.0.0",
+    )
+    .unwrap();
+    assert_eq!(
+        crate::contract::migrate(deps.as_mut(), mock_env(), MigrateMsg {})
+            .unwrap_err(),
+        generic_err(&format!(
+            "Cannot downgrade from 99.0.0 to {}",
+            env!("CARGO_PKG_VERSION")
+        ))
+    );
+
+    cw2::set_contract_version(deps.as_mut().storage, "other", "0.1.0").unwrap();
+    assert_eq!(
+        crate::contract::migrate(deps.as_mut(), mock_env(), MigrateMsg {})
+            .unwrap_err(),
+        generic_err("Can only upgrade from same type")
+    );
+}
+
+#[test]
+fn proper_all_prices() {
+    let mut router = mock_app();
+    let owner = Addr::unchecked("owner");
+    let oracle_addr = create_fast_oracle(router.borrow_mut(), &owner);
+
+    for (ticker, price) in [("atom", 10u128), ("juno", 20u128)] {
+        router
+            .execute_contract(
+                owner.clone(),
+                oracle_addr.clone(),
+                &ExecuteMsg::Update {
+                    ticker: ticker.to_string(),
+                    price: Uint128::new(price),
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    router
+        .execute_contract(
+            owner.clone(),
+            oracle_addr.clone(),
+            &ExecuteMsg::Update {
+                ticker: "".to_string(),
+                price: Uint128::new(1),
+            },
+            &[],
+        )
+        .expect_err("Empty ticker should fail");
+
+    let price: PriceResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &oracle_addr,
+            &QueryMsg::Price {
+                ticker: "juno".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(price.value, Uint128::new(20));
+
+    router
+        .wrap()
+        .query_wasm_smart::<PriceResponse>(
+            &oracle_addr,
+            &QueryMsg::Price {
+                ticker: "osmo".to_string(),
+            },
+        )
+        .expect_err("Unknown ticker should fail");
+
+    let res: AllPricesResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &oracle_addr,
+            &QueryMsg::AllPrices {
+                start_after: None,
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    let tickers: Vec<String> =
+        res.prices.iter().map(|p| p.ticker.clone()).collect();
+    assert_eq!(tickers, vec!["atom"]);
+
+    let res: AllPricesResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &oracle_addr,
+            &QueryMsg::AllPrices {
+                start_after: Some("atom".to_string()),
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res.prices.len(), 1);
+    assert_eq!(res.prices[0].ticker, "juno");
+    assert_eq!(res.prices[0].price, Uint128::new(20));
+}
+
+#[test]
+fn proper_migrate_legacy_price() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    cw2::set_contract_version(
+        deps.as_mut().storage,
+        "deliverdao:fast_oracle",
+        "0.1.0",
+    )
+    .unwrap();
+    LEGACY_PRICE.save(deps.as_mut().storage, &42u128).unwrap();
+    ADMIN
+        .save(deps.as_mut().storage, &Addr::unchecked("owner"))
+        .unwrap();
+
+    crate::contract::migrate(deps.as_mut(), env.clone(), MigrateMsg {})
+        .unwrap();
+
+    let entry = PRICES.load(&deps.storage, DEFAULT_TICKER).unwrap();
+    assert_eq!(entry.price, Uint128::new(42));
+    assert_eq!(entry.updated_at, env.block.time);
+    assert_eq!(entry.triggered_by, Addr::unchecked("owner"));
+    assert!(LEGACY_PRICE.may_load(&deps.storage).unwrap().is_none());
+}
+
+#[test]
+fn proper_price_history() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let start = env.block.time;
+    let info = mock_info("owner", &[]);
+
+    crate::contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        InstantiateMsg {},
+    )
+    .unwrap();
+
+    for price in [100u128, 110, 120] {
+        env.block.time = env.block.time.plus_seconds(60);
+        env.block.height += 10;
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::Update {
+                ticker: "atom".to_string(),
+                price: Uint128::new(price),
+            },
+        )
+        .unwrap();
+    }
+
+    let query_price_at = |time| -> Option<PriceEntry> {
+        let res: PriceAtResponse = from_binary(
+            &crate::contract::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PriceAt {
+                    ticker: "atom".to_string(),
+                    time,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.entry
+    };
+
+    /* Nothing published for the ticker yet */
+    assert!(query_price_at(start.plus_seconds(59)).is_none());
+
+    let entry = query_price_at(start.plus_seconds(60)).unwrap();
+    assert_eq!(entry.price, Uint128::new(100));
+    assert_eq!(entry.triggered_by, Addr::unchecked("owner"));
+
+    let entry = query_price_at(start.plus_seconds(150)).unwrap();
+    assert_eq!(entry.price, Uint128::new(110));
+    assert_eq!(entry.updated_at, start.plus_seconds(120));
+    assert_eq!(entry.height, mock_env().block.height + 20);
+
+    let entry = query_price_at(start.plus_seconds(1_000)).unwrap();
+    assert_eq!(entry.price, Uint128::new(120));
+
+    let res: PriceHistoryResponse = from_binary(
+        &crate::contract::query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PriceHistory {
+                ticker: "atom".to_string(),
+                start_after: Some(start.plus_seconds(60)),
+                limit: Some(1),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.entries.len(), 1);
+    assert_eq!(res.entries[0].price, Uint128::new(110));
+}
+
+#[test]
+fn proper_twap_and_candles() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let start = env.block.time;
+    let info = mock_info("owner", &[]);
+
+    crate::contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        InstantiateMsg {},
+    )
+    .unwrap();
+
+    for (seconds, price) in
+        [(60u64, 100u128), (120, 110), (130, 130), (180, 120)]
+    {
+        env.block.time = start.plus_seconds(seconds);
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::Update {
+                ticker: "atom".to_string(),
+                price: Uint128::new(price),
+            },
+        )
+        .unwrap();
+    }
+
+    let query_twap = |from: u64, to: u64| -> StdResult<Option<Uint128>> {
+        let res: TwapResponse = from_binary(&crate::contract::query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Twap {
+                ticker: "atom".to_string(),
+                start: start.plus_seconds(from),
+                end: start.plus_seconds(to),
+            },
+        )?)?;
+        Ok(res.twap)
+    };
+
+    /* (100 * 60 + 110 * 10 + 130 * 50) / 120 */
+    assert_eq!(query_twap(60, 180).unwrap(), Some(Uint128::new(113)));
+    /* (100 * 30 + 110 * 10 + 130 * 50 + 120 * 60) / 150 */
+    assert_eq!(query_twap(90, 240).unwrap(), Some(Uint128::new(118)));
+    /* A window within one price is that price */
+    assert_eq!(query_twap(200, 300).unwrap(), Some(Uint128::new(120)));
+
+    /* Nothing published at the start */
+    assert_eq!(query_twap(0, 120).unwrap(), None);
+    /* Empty window */
+    assert_eq!(
+        query_twap(120, 120).unwrap_err(),
+        StdError::generic_err(
+            "The TWAP window must end at least one second after it starts"
+        )
+    );
+
+    let query_candles = |start_after, limit| -> CandlesResponse {
+        from_binary(
+            &crate::contract::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Candles {
+                    ticker: "atom".to_string(),
+                    interval: 60,
+                    start_after,
+                    limit,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    let open_time = |seconds: u64| {
+        Timestamp::from_seconds(start.plus_seconds(seconds).seconds() / 60 * 60)
+    };
+
+    let res = query_candles(None, None);
+    assert_eq!(res.candles.len(), 3);
+    assert_eq!(
+        res.candles[1],
+        Candle {
+            open_time: open_time(120),
+            open: Uint128::new(110),
+            high: Uint128::new(130),
+            low: Uint128::new(110),
+            close: Uint128::new(130),
+        }
+    );
+    assert_eq!(res.candles[2].open_time, open_time(180));
+
+    let res = query_candles(Some(res.candles[0].open_time), Some(1));
+    assert_eq!(res.candles.len(), 1);
+    assert_eq!(res.candles[0].open_time, open_time(120));
+
+    /* A zero interval has no candles */
+    assert!(crate::contract::query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Candles {
+            ticker: "atom".to_string(),
+            interval: 0,
+            start_after: None,
+            limit: None,
+        },
+    )
+    .is_err());
+}
+
+#[test]
+fn proper_feeder_median() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let owner = mock_info("owner", &[]);
+    let feeder_a = mock_info("feeder_a", &[]);
+    let feeder_b = mock_info("feeder_b", &[]);
+
+    crate::contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        InstantiateMsg {},
+    )
+    .unwrap();
+
+    for feeder in ["feeder_a", "feeder_b"] {
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            owner.clone(),
+            ExecuteMsg::AddFeeder {
+                feeder: Addr::unchecked(feeder),
+            },
+        )
+        .unwrap();
+    }
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            feeder_a.clone(),
+            ExecuteMsg::AddFeeder {
+                feeder: Addr::unchecked("faker"),
+            },
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+
+    let update_config = |quorum| ExecuteMsg::UpdateConfig {
+        quorum: Some(quorum),
+        max_submission_age_seconds: Some(Uint128::new(60)),
+        max_deviation: None,
+        min_update_interval_seconds: None,
+    };
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            owner.clone(),
+            update_config(4),
+        )
+        .unwrap_err(),
+        generic_err("The quorum cannot exceed the number of feeders")
+    );
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        update_config(2),
+    )
+    .unwrap();
+
+    /* Older oracles seeded a zero price, it never counts */
+    SUBMISSIONS
+        .save(
+            deps.as_mut().storage,
+            (&Addr::unchecked("owner"), "atom"),
+            &PriceEntry {
+                price: Uint128::zero(),
+                updated_at: env.block.time,
+                height: env.block.height,
+                triggered_by: Addr::unchecked("owner"),
+                expo: 0,
+            },
+        )
+        .unwrap();
+
+    let update = |price| ExecuteMsg::Update {
+        ticker: "atom".to_string(),
+        price: Uint128::new(price),
+    };
+    let query_price = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, env| {
+        crate::contract::query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Price {
+                ticker: "atom".to_string(),
+            },
+        )
+        .map(|res| from_binary::<PriceResponse>(&res).unwrap().value)
+    };
+
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        feeder_a.clone(),
+        update(100),
+    )
+    .unwrap();
+    /* Below quorum */
+    assert_eq!(
+        query_price(&deps, env.clone()).unwrap_err(),
+        StdError::generic_err("Only 1 of 2 feeders reported atom")
+    );
+
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        feeder_b.clone(),
+        update(120),
+    )
+    .unwrap();
+    assert_eq!(query_price(&deps, env.clone()).unwrap(), Uint128::new(110));
+
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        update(300),
+    )
+    .unwrap();
+    assert_eq!(query_price(&deps, env.clone()).unwrap(), Uint128::new(120));
+
+    /* Only the owner's submission is fresh anymore */
+    env.block.time = env.block.time.plus_seconds(61);
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        update(200),
+    )
+    .unwrap();
+    assert_eq!(
+        query_price(&deps, env.clone()).unwrap_err(),
+        StdError::generic_err("Only 1 of 2 feeders reported atom")
+    );
+
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("faker", &[]),
+            update(1),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        ExecuteMsg::RemoveFeeder {
+            feeder: Addr::unchecked("feeder_b"),
+        },
+    )
+    .unwrap();
+    /* Two feeders left for a quorum of two */
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            owner,
+            ExecuteMsg::RemoveFeeder {
+                feeder: Addr::unchecked("feeder_a"),
+            },
+        )
+        .unwrap_err(),
+        generic_err("Removing the feeder would make the quorum unreachable")
+    );
+
+    let res: FeedersResponse = from_binary(
+        &crate::contract::query(deps.as_ref(), env, QueryMsg::Feeders {})
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.feeders,
+        vec![Addr::unchecked("feeder_a"), Addr::unchecked("owner")]
+    );
+}
+
+#[test]
+fn proper_deviation_guard() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let owner = mock_info("owner", &[]);
+    let feeder = mock_info("feeder", &[]);
+
+    crate::contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        InstantiateMsg {},
+    )
+    .unwrap();
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        ExecuteMsg::AddFeeder {
+            feeder: Addr::unchecked("feeder"),
+        },
+    )
+    .unwrap();
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        ExecuteMsg::UpdateConfig {
+            quorum: None,
+            max_submission_age_seconds: None,
+            max_deviation: Some(Decimal::percent(10)),
+            min_update_interval_seconds: Some(Uint128::new(30)),
+        },
+    )
+    .unwrap();
+
+    let update = |price| ExecuteMsg::Update {
+        ticker: "atom".to_string(),
+        price: Uint128::new(price),
+    };
+    let query_price = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| {
+        from_binary::<PriceResponse>(
+            &crate::contract::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Price {
+                    ticker: "atom".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .value
+    };
+
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            feeder.clone(),
+            update(0),
+        )
+        .unwrap_err(),
+        generic_err("The price cannot be zero")
+    );
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        feeder.clone(),
+        update(100),
+    )
+    .unwrap();
+    /* Too soon */
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            feeder.clone(),
+            update(105),
+        )
+        .unwrap_err(),
+        generic_err("Updates of atom are limited to one every 30 second(s)")
+    );
+
+    /* Fat finger, held back */
+    env.block.time = env.block.time.plus_seconds(30);
+    let res = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        feeder.clone(),
+        update(1_000),
+    )
+    .unwrap();
+    assert_eq!(res.events[0].attributes[0].key, "price_pending");
+    assert_eq!(query_price(&deps), Uint128::new(100));
+
+    let res: PendingPriceResponse = from_binary(
+        &crate::contract::query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PendingPrice {
+                ticker: "atom".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.pending.len(), 1);
+    let pending = &res.pending[0];
+    assert_eq!(pending.price, Uint128::new(1_000));
+    assert_eq!(pending.previous_price, Uint128::new(100));
+
+    /* The submitter can't confirm its own update */
+    let confirm = ExecuteMsg::ConfirmPrice {
+        ticker: "atom".to_string(),
+        feeder: Addr::unchecked("feeder"),
+    };
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            feeder.clone(),
+            confirm.clone(),
+        )
+        .unwrap_err(),
+        generic_err("A second feeder has to confirm the price")
+    );
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("faker", &[]),
+            confirm.clone(),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        confirm.clone(),
+    )
+    .unwrap();
+    assert_eq!(query_price(&deps), Uint128::new(1_000));
+
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            owner.clone(),
+            confirm,
+        )
+        .unwrap_err(),
+        generic_err("No pending price for atom from feeder")
+    );
+
+    env.block.time = env.block.time.plus_seconds(30);
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        feeder.clone(),
+        update(5_000),
+    )
+    .unwrap();
+    /* Held back updates are rate limited like published ones */
+    env.block.time = env.block.time.plus_seconds(10);
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            feeder.clone(),
+            update(5_000),
+        )
+        .unwrap_err(),
+        generic_err("Updates of atom are limited to one every 30 second(s)")
+    );
+
+    /* A second feeder sending the same price confirms it */
+    let res = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        update(5_000),
+    )
+    .unwrap();
+    assert!(res
+        .events
+        .iter()
+        .any(|e| e.attributes[0].key == "price_confirmed"));
+    assert_eq!(query_price(&deps), Uint128::new(5_000));
+
+    /* A removed feeder's held back price can't be confirmed anymore */
+    env.block.time = env.block.time.plus_seconds(30);
+    crate::contract::execute(deps.as_mut(), env.clone(), feeder, update(50))
+        .unwrap();
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        ExecuteMsg::RemoveFeeder {
+            feeder: Addr::unchecked("feeder"),
+        },
+    )
+    .unwrap();
+    let res = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        update(50),
+    )
+    .unwrap();
+    assert_eq!(res.events[0].attributes[0].key, "price_pending");
+
+    let res: PendingPriceResponse = from_binary(
+        &crate::contract::query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PendingPrice {
+                ticker: "atom".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.pending.len(), 1);
+    assert_eq!(res.pending[0].feeder, Addr::unchecked("owner"));
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env,
+            owner,
+            ExecuteMsg::ConfirmPrice {
+                ticker: "atom".to_string(),
+                feeder: Addr::unchecked("feeder"),
+            },
+        )
+        .unwrap_err(),
+        generic_err("No pending price for atom from feeder")
+    );
+}
+
+#[test]
+fn proper_submit_signed() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let owner = mock_info("owner", &[]);
+    let relayer = mock_info("relayer", &[]);
+
+    /* ed25519 key and signatures made off-chain for these exact payloads */
+    let pubkey =
+        Binary::from_base64("6kpsY+KcUgq+9VB7Ey7F+ZVHdq6+vnuSQh7qaRRG0iw=")
+            .unwrap();
+    let payload = Binary::from(
+        br#"{"publisher":"feeder","chain_id":"cosmos-testnet-14002","contract":"cosmos2contract","ticker":"atom","price":"100","timestamp":"1571797419879305533"}"#.as_slice(),
+    );
+    let signature = Binary::from_base64(
+        "b/9NggYua93PmSX34C5EsDOImcLkcglskeZfFc8Nqu9SSQ8D0oNnjdwQXhL0Bgn9Uq8F8mh2YAj69dVdvNgFBg==",
+    )
+    .unwrap();
+    let older_payload = Binary::from(
+        br#"{"publisher":"feeder","chain_id":"cosmos-testnet-14002","contract":"cosmos2contract","ticker":"atom","price":"90","timestamp":"1571797409879305533"}"#.as_slice(),
+    );
+    let older_signature = Binary::from_base64(
+        "bR4RpZ/zj7T+xlQav00PNiLLLeuHQLwE6IKX0JYRPBrhZizUEjvmsMzodi1tnH84VNBRXBs9b0m2MLYQo1GUDw==",
+    )
+    .unwrap();
+    let foreign_payload = Binary::from(
+        br#"{"publisher":"feeder","chain_id":"other-chain","contract":"cosmos2contract","ticker":"atom","price":"95","timestamp":"1571797414879305533"}"#.as_slice(),
+    );
+    let foreign_signature = Binary::from_base64(
+        "voXN6V537m/8XBcsDPocNljr1m4AjmLBFaztTatY6RmJ2xO0+bkDltmgto3/N7EPyS+ZrkfBfO8dXH5FYl+JAQ==",
+    )
+    .unwrap();
+
+    crate::contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        InstantiateMsg {},
+    )
+    .unwrap();
+
+    let submit =
+        |payload: &Binary, signature: &Binary| ExecuteMsg::SubmitSigned {
+            payload: payload.clone(),
+            signature: signature.clone(),
+        };
+
+    /* No key registered yet */
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            relayer.clone(),
+            submit(&payload, &signature),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        ExecuteMsg::AddFeeder {
+            feeder: Addr::unchecked("feeder"),
+        },
+    )
+    .unwrap();
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        ExecuteMsg::SetPublisherKey {
+            feeder: Addr::unchecked("feeder"),
+            key: PublisherKey {
+                pubkey,
+                scheme: KeyScheme::Ed25519,
+            },
+        },
+    )
+    .unwrap();
+
+    /* Payloads could be relayed forever without a maximum age */
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            relayer.clone(),
+            submit(&older_payload, &older_signature),
+        )
+        .unwrap_err(),
+        generic_err("Signed payloads need a maximum submission age")
+    );
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner,
+        ExecuteMsg::UpdateConfig {
+            quorum: None,
+            max_submission_age_seconds: Some(Uint128::new(60)),
+            max_deviation: None,
+            min_update_interval_seconds: None,
+        },
+    )
+    .unwrap();
+
+    /* Signed for another chain */
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            relayer.clone(),
+            submit(&foreign_payload, &foreign_signature),
+        )
+        .unwrap_err(),
+        generic_err("The payload is meant for another oracle")
+    );
+
+    /* Signature of another payload */
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            relayer.clone(),
+            submit(&payload, &older_signature),
+        )
+        .unwrap_err(),
+        generic_err("Invalid payload signature")
+    );
+
+    /* Relayed after the maximum age */
+    let mut late_env = env.clone();
+    late_env.block.time = env.block.time.plus_seconds(60);
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            late_env,
+            relayer.clone(),
+            submit(&older_payload, &older_signature),
+        )
+        .unwrap_err(),
+        generic_err("The payload is too old")
+    );
+
+    /* Signed after the current block */
+    let mut early_env = env.clone();
+    early_env.block.time = env.block.time.minus_seconds(1);
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            early_env,
+            relayer.clone(),
+            submit(&payload, &signature),
+        )
+        .unwrap_err(),
+        generic_err("The payload is from the future")
+    );
+
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        relayer.clone(),
+        submit(&older_payload, &older_signature),
+    )
+    .unwrap();
+    /* Published at the block time, the signed time only dates the submission */
+    let latest = PRICES.load(&deps.storage, "atom").unwrap();
+    assert_eq!(latest.updated_at, env.block.time);
+    let submission = SUBMISSIONS
+        .load(&deps.storage, (&Addr::unchecked("feeder"), "atom"))
+        .unwrap();
+    assert_eq!(submission.updated_at, env.block.time.minus_seconds(10));
+
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        relayer.clone(),
+        submit(&payload, &signature),
+    )
+    .unwrap();
+
+    let price: PriceResponse = from_binary(
+        &crate::contract::query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Price {
+                ticker: "atom".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(price.value, Uint128::new(100));
+
+    /* Replayed and out of order payloads */
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            relayer.clone(),
+            submit(&payload, &signature),
+        )
+        .unwrap_err(),
+        generic_err("The payload is older than the last one submitted")
+    );
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env,
+            relayer,
+            submit(&older_payload, &older_signature),
+        )
+        .unwrap_err(),
+        generic_err("The payload is older than the last one submitted")
+    );
+}
+
+#[test]
+fn proper_feed_info() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let owner = mock_info("owner", &[]);
+
+    crate::contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        InstantiateMsg {},
+    )
+    .unwrap();
+
+    let query = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, msg| {
+        crate::contract::query(deps.as_ref(), mock_env(), msg)
+    };
+    assert_eq!(
+        query(
+            &deps,
+            QueryMsg::FeedInfo {
+                ticker: "atom".to_string(),
+            },
+        )
+        .unwrap_err(),
+        StdError::generic_err("No feed info for atom")
+    );
+
+    let feed_info = FeedInfo {
+        expo: -6,
+        description: "ATOM / USD".to_string(),
+        base: "ATOM".to_string(),
+        quote: "USD".to_string(),
+    };
+    let set_feed_info = ExecuteMsg::SetFeedInfo {
+        ticker: "atom".to_string(),
+        info: feed_info.clone(),
+    };
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("faker", &[]),
+            set_feed_info.clone(),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        set_feed_info,
+    )
+    .unwrap();
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        ExecuteMsg::Update {
+            ticker: "atom".to_string(),
+            price: Uint128::new(12_345_678),
+        },
+    )
+    .unwrap();
+
+    let info: FeedInfoResponse = from_binary(
+        &query(
+            &deps,
+            QueryMsg::FeedInfo {
+                ticker: "atom".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(info.base, "ATOM");
+    assert_eq!(info.quote, "USD");
+
+    let price: PriceResponse = from_binary(
+        &query(
+            &deps,
+            QueryMsg::Price {
+                ticker: "atom".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(price.value, Uint128::new(12_345_678));
+    assert_eq!(price.expo, -6);
+    assert_eq!(price.timestamp, env.block.time);
+
+    let res: PriceAtResponse = from_binary(
+        &query(
+            &deps,
+            QueryMsg::PriceAt {
+                ticker: "atom".to_string(),
+                time: env.block.time,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.entry.unwrap().expo, -6);
+
+    /* The description can change, the exponent can't anymore */
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        ExecuteMsg::SetFeedInfo {
+            ticker: "atom".to_string(),
+            info: FeedInfo {
+                description: "Cosmos Hub / USD".to_string(),
+                ..feed_info.clone()
+            },
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env,
+            owner,
+            ExecuteMsg::SetFeedInfo {
+                ticker: "atom".to_string(),
+                info: FeedInfo {
+                    expo: -8,
+                    ..feed_info
+                },
+            },
+        )
+        .unwrap_err(),
+        generic_err("The exponent of atom cannot change once it has prices")
+    );
+}
+
+#[test]
+fn proper_subscriber_callbacks() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let owner = mock_info("owner", &[]);
+
+    crate::contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        InstantiateMsg {},
+    )
+    .unwrap();
+
+    let subscribe = ExecuteMsg::AddSubscriber {
+        contract: Addr::unchecked("market"),
+        tickers: vec!["atom".to_string()],
+        gas_limit: Some(500_000),
+    };
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("faker", &[]),
+            subscribe.clone(),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        subscribe.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            owner.clone(),
+            subscribe,
+        )
+        .unwrap_err(),
+        generic_err("market is already subscribed")
+    );
+
+    let update = |ticker: &str| ExecuteMsg::Update {
+        ticker: ticker.to_string(),
+        price: Uint128::new(100),
+    };
+    let res = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        update("atom"),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].reply_on, ReplyOn::Error);
+    assert_eq!(res.messages[0].gas_limit, Some(500_000));
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "market".to_string(),
+            msg: to_binary(&CallbackMsg::OraclePriceUpdate {
+                ticker: "atom".to_string(),
+                price: Uint128::new(100),
+                expo: 0,
+                timestamp: env.block.time,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    /* Not a ticker the market follows, the vault gets the default gas */
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        ExecuteMsg::AddSubscriber {
+            contract: Addr::unchecked("vault"),
+            tickers: vec!["juno".to_string()],
+            gas_limit: None,
+        },
+    )
+    .unwrap();
+    let res = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        update("juno"),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].gas_limit, Some(300_000));
+
+    /* A failing subscriber doesn't revert the update */
+    let res = crate::contract::reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: 0,
+            result: SubMsgResult::Err("out of gas".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.events[0].attributes[0],
+        Attribute::new("callback_failed", "market")
+    );
+
+    for contract in ["market", "vault"] {
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            owner.clone(),
+            ExecuteMsg::RemoveSubscriber {
+                contract: Addr::unchecked(contract),
+            },
+        )
+        .unwrap();
+    }
+    let res: SubscribersResponse = from_binary(
+        &crate::contract::query(deps.as_ref(), env, QueryMsg::Subscribers {})
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(res.subscribers.is_empty());
+}
+
+#[test]
+fn proper_ownership_transfer() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let owner = mock_info("owner", &[]);
+    let new_owner = mock_info("new_owner", &[]);
+
+    crate::contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        InstantiateMsg {},
+    )
+    .unwrap();
+
+    let query_owner = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| {
+        from_binary::<OwnerResponse>(
+            &crate::contract::query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Owner {},
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .owner
+    };
+    let query_pending_owner = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| {
+        from_binary::<PendingOwnerResponse>(
+            &crate::contract::query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::PendingOwner {},
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .pending_owner
+    };
+
+    assert_eq!(query_owner(&deps), Addr::unchecked("owner"));
+    assert_eq!(query_pending_owner(&deps), None);
+
+    /* Nobody can accept before a proposal, only the owner proposes */
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            new_owner.clone(),
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            new_owner.clone(),
+            ExecuteMsg::ProposeOwner {
+                owner: Addr::unchecked("new_owner"),
+            },
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+
+    let res = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        ExecuteMsg::ProposeOwner {
+            owner: Addr::unchecked("new_owner"),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.events[0].attributes[0],
+        Attribute::new("owner_proposed", "new_owner")
+    );
+    assert_eq!(query_owner(&deps), Addr::unchecked("owner"));
+    assert_eq!(
+        query_pending_owner(&deps),
+        Some(Addr::unchecked("new_owner"))
+    );
+
+    /* Only the proposed owner can accept */
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            owner.clone(),
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+
+    let res = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        new_owner.clone(),
+        ExecuteMsg::AcceptOwnership {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.events[0].attributes,
+        vec![
+            Attribute::new("owner_changed", "new_owner"),
+            Attribute::new("previous_owner", "owner"),
+        ]
+    );
+    assert_eq!(query_owner(&deps), Addr::unchecked("new_owner"));
+    assert_eq!(query_pending_owner(&deps), None);
+
+    /* The previous owner lost its rights, a proposal can be withdrawn */
+    assert_eq!(
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            owner.clone(),
+            ExecuteMsg::ProposeOwner {
+                owner: Addr::unchecked("owner"),
+            },
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        new_owner.clone(),
+        ExecuteMsg::ProposeOwner {
+            owner: Addr::unchecked("typo"),
+        },
+    )
+    .unwrap();
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        new_owner,
+        ExecuteMsg::CancelOwnershipProposal {},
+    )
+    .unwrap();
+    assert_eq!(query_pending_owner(&deps), None);
+
+    /* Price updates are reported to indexers */
+    let res = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        owner,
+        ExecuteMsg::Update {
+            ticker: "atom".to_string(),
+            price: Uint128::new(100),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.events[0].attributes,
+        vec![
+            Attribute::new("price_submitted", "atom"),
+            Attribute::new("price", "100"),
+            Attribute::new("feeder", "owner"),
+        ]
+    );
+    assert_eq!(
+        res.events[1].attributes,
+        vec![
+            Attribute::new("price_updated", "atom"),
+            Attribute::new("price", "100"),
+            Attribute::new("expo", "0"),
+            Attribute::new("timestamp", env.block.time.nanos().to_string()),
+            Attribute::new("height", env.block.height.to_string()),
+        ]
+    );
+}
@@ -1,15 +1,82 @@
 /**
-* This file was automatically generated by @cosmwasm/ts-codegen@0.19.0.
+* This file was automatically generated by @cosmwasm/ts-codegen@0.20.0.
 * DO NOT MODIFY IT BY HAND. Instead, modify the source JSONSchema file,
 * and run the @cosmwasm/ts-codegen generate command to regenerate this file.
 */
 
 import { CosmWasmClient, SigningCosmWasmClient, ExecuteResult } from "@cosmjs/cosmwasm-stargate";
 import { Coin, StdFee } from "@cosmjs/amino";
-import { ExecuteMsg, Uint128, Addr, InstantiateMsg, QueryMsg } from "./FastOracle.types";
+import { Uint128, Timestamp, Uint64, AllPricesResponse, TickerPrice, CallbackMsg, CandlesResponse, Candle, Decimal, Config, ExecuteMsg, Addr, Binary, KeyScheme, PublisherKey, FeedInfo, FeedersResponse, InstantiateMsg, MigrateMsg, OwnerResponse, PendingOwnerResponse, PendingPriceResponse, PendingPrice, PriceAtResponse, PriceEntry, PriceHistoryResponse, PriceResponse, PublisherKeyResponse, QueryMsg, SubscribersResponse, Subscriber, TwapResponse } from "./FastOracle.types";
 export interface FastOracleReadOnlyInterface {
   contractAddress: string;
-  price: () => Promise<PriceResponse>;
+  price: ({
+    ticker
+  }: {
+    ticker: string;
+  }) => Promise<PriceResponse>;
+  allPrices: ({
+    limit,
+    startAfter
+  }: {
+    limit?: number;
+    startAfter?: string;
+  }) => Promise<AllPricesResponse>;
+  priceAt: ({
+    ticker,
+    time
+  }: {
+    ticker: string;
+    time: Timestamp;
+  }) => Promise<PriceAtResponse>;
+  priceHistory: ({
+    limit,
+    startAfter,
+    ticker
+  }: {
+    limit?: number;
+    startAfter?: Timestamp;
+    ticker: string;
+  }) => Promise<PriceHistoryResponse>;
+  twap: ({
+    end,
+    start,
+    ticker
+  }: {
+    end: Timestamp;
+    start: Timestamp;
+    ticker: string;
+  }) => Promise<TwapResponse>;
+  candles: ({
+    interval,
+    limit,
+    startAfter,
+    ticker
+  }: {
+    interval: number;
+    limit?: number;
+    startAfter?: Timestamp;
+    ticker: string;
+  }) => Promise<CandlesResponse>;
+  config: () => Promise<ConfigResponse>;
+  owner: () => Promise<OwnerResponse>;
+  pendingOwner: () => Promise<PendingOwnerResponse>;
+  feeders: () => Promise<FeedersResponse>;
+  pendingPrice: ({
+    ticker
+  }: {
+    ticker: string;
+  }) => Promise<PendingPriceResponse>;
+  publisherKey: ({
+    feeder
+  }: {
+    feeder: Addr;
+  }) => Promise<PublisherKeyResponse>;
+  feedInfo: ({
+    ticker
+  }: {
+    ticker: string;
+  }) => Promise<FeedInfoResponse>;
+  subscribers: () => Promise<SubscribersResponse>;
 }
 export class FastOracleQueryClient implements FastOracleReadOnlyInterface {
   client: CosmWasmClient;
@@ -19,11 +86,170 @@ export class FastOracleQueryClient implements FastOracleReadOnlyInterface {
     this.client = client;
     this.contractAddress = contractAddress;
     this.price = this.price.bind(this);
+    this.allPrices = this.allPrices.bind(this);
+    this.priceAt = this.priceAt.bind(this);
+    this.priceHistory = this.priceHistory.bind(this);
+    this.twap = this.twap.bind(this);
+    this.candles = this.candles.bind(this);
+    this.config = this.config.bind(this);
+    this.owner = this.owner.bind(this);
+    this.pendingOwner = this.pendingOwner.bind(this);
+    this.feeders = this.feeders.bind(this);
+    this.pendingPrice = this.pendingPrice.bind(this);
+    this.publisherKey = this.publisherKey.bind(this);
+    this.feedInfo = this.feedInfo.bind(this);
+    this.subscribers = this.subscribers.bind(this);
   }
 
-  price = async (): Promise<PriceResponse> => {
+  price = async ({
+    ticker
+  }: {
+    ticker: string;
+  }): Promise<PriceResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      price: {
+        ticker
+      }
+    });
+  };
+  allPrices = async ({
+    limit,
+    startAfter
+  }: {
+    limit?: number;
+    startAfter?: string;
+  }): Promise<AllPricesResponse> => {
     return this.client.queryContractSmart(this.contractAddress, {
-      price: {}
+      all_prices: {
+        limit,
+        start_after: startAfter
+      }
+    });
+  };
+  priceAt = async ({
+    ticker,
+    time
+  }: {
+    ticker: string;
+    time: Timestamp;
+  }): Promise<PriceAtResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      price_at: {
+        ticker,
+        time
+      }
+    });
+  };
+  priceHistory = async ({
+    limit,
+    startAfter,
+    ticker
+  }: {
+    limit?: number;
+    startAfter?: Timestamp;
+    ticker: string;
+  }): Promise<PriceHistoryResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      price_history: {
+        limit,
+        start_after: startAfter,
+        ticker
+      }
+    });
+  };
+  twap = async ({
+    end,
+    start,
+    ticker
+  }: {
+    end: Timestamp;
+    start: Timestamp;
+    ticker: string;
+  }): Promise<TwapResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      twap: {
+        end,
+        start,
+        ticker
+      }
+    });
+  };
+  candles = async ({
+    interval,
+    limit,
+    startAfter,
+    ticker
+  }: {
+    interval: number;
+    limit?: number;
+    startAfter?: Timestamp;
+    ticker: string;
+  }): Promise<CandlesResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      candles: {
+        interval,
+        limit,
+        start_after: startAfter,
+        ticker
+      }
+    });
+  };
+  config = async (): Promise<ConfigResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      config: {}
+    });
+  };
+  owner = async (): Promise<OwnerResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      owner: {}
+    });
+  };
+  pendingOwner = async (): Promise<PendingOwnerResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      pending_owner: {}
+    });
+  };
+  feeders = async (): Promise<FeedersResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      feeders: {}
+    });
+  };
+  pendingPrice = async ({
+    ticker
+  }: {
+    ticker: string;
+  }): Promise<PendingPriceResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      pending_price: {
+        ticker
+      }
+    });
+  };
+  publisherKey = async ({
+    feeder
+  }: {
+    feeder: Addr;
+  }): Promise<PublisherKeyResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      publisher_key: {
+        feeder
+      }
+    });
+  };
+  feedInfo = async ({
+    ticker
+  }: {
+    ticker: string;
+  }): Promise<FeedInfoResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      feed_info: {
+        ticker
+      }
+    });
+  };
+  subscribers = async (): Promise<SubscribersResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      subscribers: {}
     });
   };
 }
@@ -31,15 +257,87 @@ export interface FastOracleInterface extends FastOracleReadOnlyInterface {
   contractAddress: string;
   sender: string;
   update: ({
-    price
+    price,
+    ticker
   }: {
     price: Uint128;
+    ticker: string;
   }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
-  owner: ({
+  proposeOwner: ({
     owner
   }: {
     owner: Addr;
   }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  acceptOwnership: (fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  cancelOwnershipProposal: (fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  addFeeder: ({
+    feeder
+  }: {
+    feeder: Addr;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  removeFeeder: ({
+    feeder
+  }: {
+    feeder: Addr;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  updateConfig: ({
+    maxDeviation,
+    maxSubmissionAgeSeconds,
+    minUpdateIntervalSeconds,
+    quorum
+  }: {
+    maxDeviation?: Decimal;
+    maxSubmissionAgeSeconds?: Uint128;
+    minUpdateIntervalSeconds?: Uint128;
+    quorum?: number;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  confirmPrice: ({
+    feeder,
+    ticker
+  }: {
+    feeder: Addr;
+    ticker: string;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  setPublisherKey: ({
+    feeder,
+    key
+  }: {
+    feeder: Addr;
+    key: PublisherKey;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  removePublisherKey: ({
+    feeder
+  }: {
+    feeder: Addr;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  submitSigned: ({
+    payload,
+    signature
+  }: {
+    payload: Binary;
+    signature: Binary;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  setFeedInfo: ({
+    info,
+    ticker
+  }: {
+    info: FeedInfo;
+    ticker: string;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  addSubscriber: ({
+    contract,
+    gasLimit,
+    tickers
+  }: {
+    contract: Addr;
+    gasLimit?: number;
+    tickers: string[];
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  removeSubscriber: ({
+    contract
+  }: {
+    contract: Addr;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
 }
 export class FastOracleClient extends FastOracleQueryClient implements FastOracleInterface {
   client: SigningCosmWasmClient;
@@ -52,29 +350,191 @@ export class FastOracleClient extends FastOracleQueryClient implements FastOracl
     this.sender = sender;
     this.contractAddress = contractAddress;
     this.update = this.update.bind(this);
-    this.owner = this.owner.bind(this);
+    this.proposeOwner = this.proposeOwner.bind(this);
+    this.acceptOwnership = this.acceptOwnership.bind(this);
+    this.cancelOwnershipProposal = this.cancelOwnershipProposal.bind(this);
+    this.addFeeder = this.addFeeder.bind(this);
+    this.removeFeeder = this.removeFeeder.bind(this);
+    this.updateConfig = this.updateConfig.bind(this);
+    this.confirmPrice = this.confirmPrice.bind(this);
+    this.setPublisherKey = this.setPublisherKey.bind(this);
+    this.removePublisherKey = this.removePublisherKey.bind(this);
+    this.submitSigned = this.submitSigned.bind(this);
+    this.setFeedInfo = this.setFeedInfo.bind(this);
+    this.addSubscriber = this.addSubscriber.bind(this);
+    this.removeSubscriber = this.removeSubscriber.bind(this);
   }
 
   update = async ({
-    price
+    price,
+    ticker
   }: {
     price: Uint128;
+    ticker: string;
   }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
     return await this.client.execute(this.sender, this.contractAddress, {
       update: {
-        price
+        price,
+        ticker
       }
     }, fee, memo, funds);
   };
-  owner = async ({
+  proposeOwner = async ({
     owner
   }: {
     owner: Addr;
   }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
     return await this.client.execute(this.sender, this.contractAddress, {
-      owner: {
+      propose_owner: {
         owner
       }
     }, fee, memo, funds);
   };
+  acceptOwnership = async (fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      accept_ownership: {}
+    }, fee, memo, funds);
+  };
+  cancelOwnershipProposal = async (fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      cancel_ownership_proposal: {}
+    }, fee, memo, funds);
+  };
+  addFeeder = async ({
+    feeder
+  }: {
+    feeder: Addr;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      add_feeder: {
+        feeder
+      }
+    }, fee, memo, funds);
+  };
+  removeFeeder = async ({
+    feeder
+  }: {
+    feeder: Addr;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      remove_feeder: {
+        feeder
+      }
+    }, fee, memo, funds);
+  };
+  updateConfig = async ({
+    maxDeviation,
+    maxSubmissionAgeSeconds,
+    minUpdateIntervalSeconds,
+    quorum
+  }: {
+    maxDeviation?: Decimal;
+    maxSubmissionAgeSeconds?: Uint128;
+    minUpdateIntervalSeconds?: Uint128;
+    quorum?: number;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      update_config: {
+        max_deviation: maxDeviation,
+        max_submission_age_seconds: maxSubmissionAgeSeconds,
+        min_update_interval_seconds: minUpdateIntervalSeconds,
+        quorum
+      }
+    }, fee, memo, funds);
+  };
+  confirmPrice = async ({
+    feeder,
+    ticker
+  }: {
+    feeder: Addr;
+    ticker: string;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      confirm_price: {
+        feeder,
+        ticker
+      }
+    }, fee, memo, funds);
+  };
+  setPublisherKey = async ({
+    feeder,
+    key
+  }: {
+    feeder: Addr;
+    key: PublisherKey;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      set_publisher_key: {
+        feeder,
+        key
+      }
+    }, fee, memo, funds);
+  };
+  removePublisherKey = async ({
+    feeder
+  }: {
+    feeder: Addr;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      remove_publisher_key: {
+        feeder
+      }
+    }, fee, memo, funds);
+  };
+  submitSigned = async ({
+    payload,
+    signature
+  }: {
+    payload: Binary;
+    signature: Binary;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      submit_signed: {
+        payload,
+        signature
+      }
+    }, fee, memo, funds);
+  };
+  setFeedInfo = async ({
+    info,
+    ticker
+  }: {
+    info: FeedInfo;
+    ticker: string;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      set_feed_info: {
+        info,
+        ticker
+      }
+    }, fee, memo, funds);
+  };
+  addSubscriber = async ({
+    contract,
+    gasLimit,
+    tickers
+  }: {
+    contract: Addr;
+    gasLimit?: number;
+    tickers: string[];
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      add_subscriber: {
+        contract,
+        gas_limit: gasLimit,
+        tickers
+      }
+    }, fee, memo, funds);
+  };
+  removeSubscriber = async ({
+    contract
+  }: {
+    contract: Addr;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      remove_subscriber: {
+        contract
+      }
+    }, fee, memo, funds);
+  };
 }
\ No newline at end of file
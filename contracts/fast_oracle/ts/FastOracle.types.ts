@@ -1,27 +1,294 @@
 /**
-* This file was automatically generated by @cosmwasm/ts-codegen@0.19.0.
+* This file was automatically generated by @cosmwasm/ts-codegen@0.20.0.
 * DO NOT MODIFY IT BY HAND. Instead, modify the source JSONSchema file,
 * and run the @cosmwasm/ts-codegen generate command to regenerate this file.
 */
 
+export type Uint128 = string;
+export type Timestamp = Uint64;
+export type Uint64 = string;
+export interface AllPricesResponse {
+  prices: TickerPrice[];
+  [k: string]: unknown;
+}
+export interface TickerPrice {
+  expo: number;
+  price: Uint128;
+  ticker: string;
+  updated_at: Timestamp;
+  [k: string]: unknown;
+}
+export type CallbackMsg = {
+  oracle_price_update: {
+    expo: number;
+    price: Uint128;
+    ticker: string;
+    timestamp: Timestamp;
+    [k: string]: unknown;
+  };
+};
+export interface CandlesResponse {
+  candles: Candle[];
+  interval: number;
+  ticker: string;
+  [k: string]: unknown;
+}
+export interface Candle {
+  close: Uint128;
+  high: Uint128;
+  low: Uint128;
+  open: Uint128;
+  open_time: Timestamp;
+  [k: string]: unknown;
+}
+export type Decimal = string;
+export interface Config {
+  max_deviation?: Decimal;
+  max_submission_age_seconds: Uint128;
+  min_update_interval_seconds?: Uint128;
+  quorum: number;
+  [k: string]: unknown;
+}
 export type ExecuteMsg = {
   update: {
     price: Uint128;
+    ticker: string;
     [k: string]: unknown;
   };
 } | {
-  owner: {
+  propose_owner: {
     owner: Addr;
     [k: string]: unknown;
   };
+} | {
+  accept_ownership: {
+    [k: string]: unknown;
+  };
+} | {
+  cancel_ownership_proposal: {
+    [k: string]: unknown;
+  };
+} | {
+  add_feeder: {
+    feeder: Addr;
+    [k: string]: unknown;
+  };
+} | {
+  remove_feeder: {
+    feeder: Addr;
+    [k: string]: unknown;
+  };
+} | {
+  update_config: {
+    max_deviation?: Decimal | null;
+    max_submission_age_seconds?: Uint128 | null;
+    min_update_interval_seconds?: Uint128 | null;
+    quorum?: number | null;
+    [k: string]: unknown;
+  };
+} | {
+  confirm_price: {
+    feeder: Addr;
+    ticker: string;
+    [k: string]: unknown;
+  };
+} | {
+  set_publisher_key: {
+    feeder: Addr;
+    key: PublisherKey;
+    [k: string]: unknown;
+  };
+} | {
+  remove_publisher_key: {
+    feeder: Addr;
+    [k: string]: unknown;
+  };
+} | {
+  submit_signed: {
+    payload: Binary;
+    signature: Binary;
+    [k: string]: unknown;
+  };
+} | {
+  set_feed_info: {
+    info: FeedInfo;
+    ticker: string;
+    [k: string]: unknown;
+  };
+} | {
+  add_subscriber: {
+    contract: Addr;
+    gas_limit?: number | null;
+    tickers: string[];
+    [k: string]: unknown;
+  };
+} | {
+  remove_subscriber: {
+    contract: Addr;
+    [k: string]: unknown;
+  };
 };
-export type Uint128 = string;
 export type Addr = string;
+export type Binary = string;
+export type KeyScheme = "secp256k1" | "ed25519";
+export interface PublisherKey {
+  pubkey: Binary;
+  scheme: KeyScheme;
+  [k: string]: unknown;
+}
+export interface FeedInfo {
+  base: string;
+  description: string;
+  expo: number;
+  quote: string;
+  [k: string]: unknown;
+}
+export interface FeedersResponse {
+  feeders: Addr[];
+  [k: string]: unknown;
+}
 export interface InstantiateMsg {
   [k: string]: unknown;
 }
+export interface MigrateMsg {
+  [k: string]: unknown;
+}
+export interface OwnerResponse {
+  owner: Addr;
+  [k: string]: unknown;
+}
+export interface PendingOwnerResponse {
+  pending_owner?: Addr | null;
+  [k: string]: unknown;
+}
+export interface PendingPriceResponse {
+  pending: PendingPrice[];
+  [k: string]: unknown;
+}
+export interface PendingPrice {
+  feeder: Addr;
+  previous_price: Uint128;
+  price: Uint128;
+  submitted_at: Timestamp;
+  [k: string]: unknown;
+}
+export interface PriceAtResponse {
+  entry?: PriceEntry | null;
+  [k: string]: unknown;
+}
+export interface PriceEntry {
+  expo?: number;
+  height: number;
+  price: Uint128;
+  triggered_by: Addr;
+  updated_at: Timestamp;
+  [k: string]: unknown;
+}
+export interface PriceHistoryResponse {
+  entries: PriceEntry[];
+  ticker: string;
+  [k: string]: unknown;
+}
+export interface PriceResponse {
+  expo: number;
+  timestamp: Timestamp;
+  value: Uint128;
+  [k: string]: unknown;
+}
+export interface PublisherKeyResponse {
+  key?: PublisherKey | null;
+  [k: string]: unknown;
+}
 export type QueryMsg = {
   price: {
+    ticker: string;
+    [k: string]: unknown;
+  };
+} | {
+  all_prices: {
+    limit?: number | null;
+    start_after?: string | null;
+    [k: string]: unknown;
+  };
+} | {
+  price_at: {
+    ticker: string;
+    time: Timestamp;
+    [k: string]: unknown;
+  };
+} | {
+  price_history: {
+    limit?: number | null;
+    start_after?: Timestamp | null;
+    ticker: string;
+    [k: string]: unknown;
+  };
+} | {
+  twap: {
+    end: Timestamp;
+    start: Timestamp;
+    ticker: string;
+    [k: string]: unknown;
+  };
+} | {
+  candles: {
+    interval: number;
+    limit?: number | null;
+    start_after?: Timestamp | null;
+    ticker: string;
     [k: string]: unknown;
   };
-};
\ No newline at end of file
+} | {
+  config: {
+    [k: string]: unknown;
+  };
+} | {
+  owner: {
+    [k: string]: unknown;
+  };
+} | {
+  pending_owner: {
+    [k: string]: unknown;
+  };
+} | {
+  feeders: {
+    [k: string]: unknown;
+  };
+} | {
+  pending_price: {
+    ticker: string;
+    [k: string]: unknown;
+  };
+} | {
+  publisher_key: {
+    feeder: Addr;
+    [k: string]: unknown;
+  };
+} | {
+  feed_info: {
+    ticker: string;
+    [k: string]: unknown;
+  };
+} | {
+  subscribers: {
+    [k: string]: unknown;
+  };
+};
+export interface SubscribersResponse {
+  subscribers: Subscriber[];
+  [k: string]: unknown;
+}
+export interface Subscriber {
+  contract: Addr;
+  gas_limit?: number | null;
+  tickers: string[];
+  [k: string]: unknown;
+}
+export interface TwapResponse {
+  end: Timestamp;
+  expo: number;
+  start: Timestamp;
+  ticker: string;
+  twap?: Uint128 | null;
+  [k: string]: unknown;
+}
\ No newline at end of file
@@ -1,27 +0,0 @@
-/**
-* This file was automatically generated by @cosmwasm/ts-codegen@0.20.0.
-* DO NOT MODIFY IT BY HAND. Instead, modify the source JSONSchema file,
-* and run the @cosmwasm/ts-codegen generate command to regenerate this file.
-*/
-
-export type ExecuteMsg = {
-  update: {
-    price: Uint128;
-    [k: string]: unknown;
-  };
-} | {
-  owner: {
-    owner: Addr;
-    [k: string]: unknown;
-  };
-};
-export type Uint128 = string;
-export type Addr = string;
-export interface InstantiateMsg {
-  [k: string]: unknown;
-}
-export type QueryMsg = {
-  price: {
-    [k: string]: unknown;
-  };
-};
\ No newline at end of file
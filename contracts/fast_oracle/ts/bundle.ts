@@ -4,10 +4,10 @@
 * and run the @cosmwasm/ts-codegen generate command to regenerate this file.
 */
 
-import * as _0 from "./PricePrediction.types";
-import * as _1 from "./PricePrediction.client";
+import * as _0 from "./FastOracle.types";
+import * as _1 from "./FastOracle.client";
 export namespace contracts {
-  export const PricePrediction = { ..._0,
+  export const FastOracle = { ..._0,
     ..._1
   };
 }
\ No newline at end of file
@@ -3,8 +3,14 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 use price_prediction::{
-    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
-    response::{ConfigResponse, MyCurrentPositionResponse, StatusResponse},
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg, SudoMsg},
+    response::{
+        AccumulatedFeesResponse, ConfigResponse, MyCurrentPositionResponse,
+        OddsResponse, ReferrerResponse, RoundPayoutsResponse,
+        RoundSummaryResponse, SimulateBetResponse, SolvencyResponse,
+        StatusResponse,
+    },
+    MigrateMsg,
 };
 
 fn main() {
@@ -18,9 +24,18 @@ fn main() {
     remove_schemas(&out_dir).unwrap();
 
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
+    export_schema(&schema_for!(SudoMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(StatusResponse), &out_dir);
     export_schema(&schema_for!(MyCurrentPositionResponse), &out_dir);
+    export_schema(&schema_for!(AccumulatedFeesResponse), &out_dir);
+    export_schema(&schema_for!(ReferrerResponse), &out_dir);
+    export_schema(&schema_for!(OddsResponse), &out_dir);
+    export_schema(&schema_for!(SimulateBetResponse), &out_dir);
+    export_schema(&schema_for!(SolvencyResponse), &out_dir);
+    export_schema(&schema_for!(RoundPayoutsResponse), &out_dir);
+    export_schema(&schema_for!(RoundSummaryResponse), &out_dir);
 }
@@ -1,32 +1,67 @@
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, SudoMsg};
+use crate::payout;
 use crate::response::ConfigResponse;
 use crate::state::{
-    ACCUMULATED_FEE, BEAR_BETS, BULL_BETS, CONFIG, IS_HAULTED, LIVE_ROUND,
-    MY_CLAIMED_ROUNDS, NEXT_ROUND, NEXT_ROUND_ID, ROUNDS, SETTLE_DENOM,
+    ACCUMULATED_FEE, ARCHIVED_ROUNDS, BETS, COMMITMENTS, CONDITIONAL_BETS,
+    CONFIG, FUNDING, IS_HAULTED, LEGACY_BEAR_BETS, LEGACY_BULL_BETS,
+    LEGACY_MIGRATION, LEGACY_MY_CLAIMED_ROUNDS, LEGACY_OUTSTANDING,
+    LIABILITIES, LIVE_ROUND, NEXT_ROUND, NEXT_ROUND_ID, REFERRALS, ROUNDS,
+    ROUND_PAYOUTS, SETTLE_DENOM,
 };
-use crate::{Config, Direction, PartialConfig};
+use crate::{Bet, Config, Direction, FeeTier, PartialConfig, RewardSink};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, wasm_execute, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut,
-    Env, Event, MessageInfo, QueryRequest, Response, StdError, StdResult,
-    Uint128, WasmQuery,
+    to_binary, wasm_execute, Addr, BankMsg, Binary, Coin, CosmosMsg, Decimal,
+    Deps, DepsMut, Env, Event, MessageInfo, Order, QueryRequest, Reply,
+    Response, StdError, StdResult, Storage, SubMsg, SubMsgResult, Timestamp,
+    Uint128, WasmMsg, WasmQuery,
 };
+use cw_storage_plus::Bound;
 use forecast_deliverdao::fast_oracle::msg::QueryMsg as FastOracleQueryMsg;
+use forecast_deliverdao::fast_oracle::response::{
+    PriceAtResponse, PriceResponse, TwapResponse,
+};
+use forecast_deliverdao::fast_oracle::{PriceEntry, DEFAULT_TICKER};
 use forecast_deliverdao::price_prediction::response::{
-    MyCurrentPositionResponse, StatusResponse,
+    AccumulatedFeesResponse, MyCurrentPositionResponse, OddsResponse,
+    ReferrerResponse, RoundPayoutsResponse, RoundSummaryResponse,
+    SimulateBetResponse, SolvencyResponse, StatusResponse,
 };
 use forecast_deliverdao::price_prediction::{
-    FinishedRound, LiveRound, MigrateMsg, NextRound, FEE_PRECISION,
+    Commitment, ConditionalBet, FinishedRound, LegacyMigration, Liabilities,
+    LiveRound, MigrateMsg, NextRound, Referral, RoundPayouts, FEE_PRECISION,
+};
+use semver::Version;
+use sha2::{Digest, Sha256};
+use stake_cw20::msg::{
+    QueryMsg as StakeCw20QueryMsg,
+    ReceiveMsg as Cw20StakeExternalRewardsExecuteMsg,
+    StakedBalanceAtHeightResponse,
 };
-use stake_cw20::msg::ReceiveMsg as Cw20StakeExternalRewardsExecuteMsg;
 use std::collections::HashSet;
 use std::iter::FromIterator;
 
 const CONTRACT_NAME: &str = "deliverdao:price_prediction";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const DEFAULT_PRUNE_LIMIT: u32 = 100;
+const MAX_PRUNE_LIMIT: u32 = 500;
+/* Rounds read by one Prune, including the ones it has to keep */
+const MAX_PRUNE_SCAN: usize = 2000;
+
+const DEFAULT_MIGRATE_LIMIT: u32 = 100;
+const MAX_MIGRATE_LIMIT: u32 = 500;
+
+const FUND_STAKERS_REPLY_ID: u64 = 1;
+
+/* Bounds the work of settling them when the round opens */
+const MAX_CONDITIONAL_BETS: u32 = 100;
+
+/* Unrevealed commitments settled per close, the rest wait for the next one */
+const MAX_SETTLED_COMMITMENTS: usize = 100;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -40,11 +75,19 @@ pub fn instantiate(
         .addr_validate(msg.config.fast_oracle_addr.as_ref())?;
     deps.api
         .addr_validate(msg.config.cw20_stake_external_rewards_addr.as_ref())?;
+    if let Some(sink) = &msg.config.reward_sink {
+        deps.api.addr_validate(sink.addr().as_ref())?;
+    }
+    if let Some(staking_addr) = &msg.config.fee_tier_staking_addr {
+        deps.api.addr_validate(staking_addr.as_ref())?;
+    }
+    validate_config(&msg.config)?;
 
     CONFIG.save(deps.storage, &msg.config)?;
     SETTLE_DENOM.save(deps.storage, &msg.settle_denom)?;
     NEXT_ROUND_ID.save(deps.storage, &0u128)?;
     ACCUMULATED_FEE.save(deps.storage, &0u128)?;
+    LIABILITIES.save(deps.storage, &Liabilities::default())?;
     IS_HAULTED.save(deps.storage, &false)?;
 
     Ok(Response::new())
@@ -53,15 +96,301 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(
     deps: DepsMut,
-    _env: Env,
-    MigrateMsg {}: MigrateMsg,
+    env: Env,
+    msg: MigrateMsg,
 ) -> StdResult<Response> {
     let version = cw2::get_contract_version(deps.storage)?;
     if version.contract != CONTRACT_NAME {
         return Err(StdError::generic_err("Can only upgrade from same type"));
     }
+
+    let stored_version = parse_version(&version.version)?;
+    let new_version = parse_version(CONTRACT_VERSION)?;
+    if stored_version > new_version {
+        return Err(StdError::generic_err(format!(
+            "Cannot downgrade from {} to {}",
+            stored_version, new_version
+        )));
+    }
+
+    let mut resp = Response::default().add_attributes(vec![
+        ("previous_version", stored_version.to_string()),
+        ("new_version", new_version.to_string()),
+    ]);
+
+    /* Handlers run in order, each one for the versions before it */
+    if stored_version < Version::new(0, 2, 0) {
+        /* Too much for one transaction on a busy contract */
+        LEGACY_MIGRATION.save(
+            deps.storage,
+            &LegacyMigration::Bets {
+                migrated_at: env.block.time,
+            },
+        )?;
+        resp = resp.add_attribute("legacy_migration", "pending");
+    }
+
+    if let Some(u_config) = msg.config {
+        let config = CONFIG.load(deps.storage)?;
+        let config = merge_config(deps.as_ref(), config, u_config)?;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
     cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    Ok(Response::default())
+    Ok(resp)
+}
+
+fn parse_version(version: &str) -> StdResult<Version> {
+    version
+        .parse()
+        .map_err(|e: semver::Error| StdError::generic_err(e.to_string()))
+}
+
+/*
+ * Move the bets older versions kept per direction, then rebuild the
+ * counters they did not keep from the unclaimed bets. Legacy rounds are
+ * treated as paid out except for what is still owed, so their old
+ * rounding dust stays in the contract.
+ */
+fn execute_migrate_legacy(
+    deps: DepsMut,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let mut step =
+        LEGACY_MIGRATION.may_load(deps.storage)?.ok_or_else(|| {
+            StdError::generic_err("There is nothing left to migrate")
+        })?;
+    /* Every entry visited uses up one unit */
+    let mut budget = limit
+        .unwrap_or(DEFAULT_MIGRATE_LIMIT)
+        .min(MAX_MIGRATE_LIMIT) as usize;
+
+    let mut migrated = 0;
+    let done = loop {
+        let (next, visited) = migrate_legacy_step(deps.storage, step, budget)?;
+        migrated += visited;
+        budget -= visited;
+        match next {
+            Some(next) if budget > 0 => step = next,
+            Some(next) => {
+                LEGACY_MIGRATION.save(deps.storage, &next)?;
+                break false;
+            }
+            None => {
+                LEGACY_MIGRATION.remove(deps.storage);
+                break true;
+            }
+        }
+    };
+
+    Ok(Response::new().add_event(
+        Event::new("forecast_deliverdao").add_attributes(vec![
+            ("legacy_migrated", migrated.to_string()),
+            ("legacy_migration_done", done.to_string()),
+        ]),
+    ))
+}
+
+/* One batch of `step`, with the step to continue with unless it was the last */
+fn migrate_legacy_step(
+    storage: &mut dyn Storage,
+    step: LegacyMigration,
+    limit: usize,
+) -> StdResult<(Option<LegacyMigration>, usize)> {
+    match step {
+        LegacyMigration::Bets { migrated_at } => {
+            let visited = migrate_legacy_bets(storage, migrated_at, limit)?;
+            if visited < limit {
+                return Ok((
+                    Some(LegacyMigration::Liabilities {
+                        start_after: None,
+                        liabilities: Liabilities::default(),
+                    }),
+                    visited,
+                ));
+            }
+            Ok((Some(LegacyMigration::Bets { migrated_at }), visited))
+        }
+        LegacyMigration::Liabilities {
+            start_after,
+            mut liabilities,
+        } => {
+            let start = start_after.map(|(round_id, addr)| {
+                Bound::exclusive((round_id.u128(), addr))
+            });
+            let bets: Vec<((u128, Addr), Bet)> = BETS
+                .range(storage, start, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<_>>()?;
+            let visited = bets.len();
+            let last_key = bets.last().map(|(key, _)| key.clone());
+
+            for ((round_id, _), bet) in
+                bets.into_iter().filter(|(_, b)| !b.claimed)
+            {
+                match ROUNDS.may_load(storage, round_id)? {
+                    Some(round) => {
+                        let owed = match bet.direction {
+                            Direction::Bull => payout::round_winnings(
+                                &round,
+                                bet.amount,
+                                Uint128::zero(),
+                            ),
+                            Direction::Bear => payout::round_winnings(
+                                &round,
+                                Uint128::zero(),
+                                bet.amount,
+                            ),
+                        };
+                        let (mut round_owed, mut unclaimed_shares) =
+                            LEGACY_OUTSTANDING
+                                .may_load(storage, round_id)?
+                                .unwrap_or_default();
+                        round_owed += owed;
+                        if round.winner.as_ref() == Some(&bet.direction) {
+                            unclaimed_shares += bet.amount;
+                        }
+                        LEGACY_OUTSTANDING.save(
+                            storage,
+                            round_id,
+                            &(round_owed, unclaimed_shares),
+                        )?;
+                        liabilities.bets += owed;
+                    }
+                    /* Bidding or live round */
+                    None => liabilities.bets += bet.amount,
+                }
+            }
+
+            if visited < limit {
+                LIABILITIES.save(storage, &liabilities)?;
+                return Ok((
+                    Some(LegacyMigration::Payouts { start_after: None }),
+                    visited,
+                ));
+            }
+            Ok((
+                Some(LegacyMigration::Liabilities {
+                    start_after: last_key.map(|(round_id, addr)| {
+                        (Uint128::from(round_id), addr)
+                    }),
+                    liabilities,
+                }),
+                visited,
+            ))
+        }
+        LegacyMigration::Payouts { start_after } => {
+            let start =
+                start_after.map(|round_id| Bound::exclusive(round_id.u128()));
+            let rounds: Vec<(u128, FinishedRound)> = ROUNDS
+                .range(storage, start, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<_>>()?;
+            let visited = rounds.len();
+            let last_round = rounds.last().map(|(round_id, _)| *round_id);
+
+            for (round_id, round) in rounds {
+                let (owed, unclaimed_shares) = LEGACY_OUTSTANDING
+                    .may_load(storage, round_id)?
+                    .unwrap_or_default();
+                LEGACY_OUTSTANDING.remove(storage, round_id);
+                if ROUND_PAYOUTS.has(storage, round_id) {
+                    continue;
+                }
+                ROUND_PAYOUTS.save(
+                    storage,
+                    round_id,
+                    &RoundPayouts {
+                        claimed_shares: payout::winning_shares(&round)
+                            .checked_sub(unclaimed_shares)?,
+                        paid_out: (round.bull_amount + round.bear_amount)
+                            .checked_sub(owed)?,
+                        ..RoundPayouts::default()
+                    },
+                )?;
+            }
+
+            if visited < limit {
+                return Ok((None, visited));
+            }
+            Ok((
+                Some(LegacyMigration::Payouts {
+                    start_after: last_round.map(Uint128::from),
+                }),
+                visited,
+            ))
+        }
+    }
+}
+
+/*
+ * Move up to `limit` bets of the per-direction maps into BETS. Claimed bets
+ * were removed from those maps, so whatever is left has not been claimed.
+ * Moved entries are removed, so each batch starts from the front.
+ */
+fn migrate_legacy_bets(
+    storage: &mut dyn Storage,
+    now: Timestamp,
+    limit: usize,
+) -> StdResult<usize> {
+    let mut migrated = 0;
+    for (direction, legacy_bets) in [
+        (Direction::Bull, LEGACY_BULL_BETS),
+        (Direction::Bear, LEGACY_BEAR_BETS),
+    ] {
+        let bets: Vec<((u128, Addr), u128)> = legacy_bets
+            .range(storage, None, None, Order::Ascending)
+            .take(limit - migrated)
+            .collect::<StdResult<_>>()?;
+
+        for ((round_id, addr), amount) in bets {
+            legacy_bets.remove(storage, (round_id, addr.clone()));
+            let placed_at = round_bid_time(storage, round_id)?.unwrap_or(now);
+            BETS.save(
+                storage,
+                (round_id, addr),
+                &Bet {
+                    direction: direction.clone(),
+                    amount: Uint128::from(amount),
+                    placed_at,
+                    claimed: false,
+                },
+            )?;
+            migrated += 1;
+        }
+    }
+
+    let claims: Vec<(Addr, u128)> = LEGACY_MY_CLAIMED_ROUNDS
+        .keys(storage, None, None, Order::Ascending)
+        .take(limit - migrated)
+        .collect::<StdResult<_>>()?;
+    for claim in claims {
+        LEGACY_MY_CLAIMED_ROUNDS.remove(storage, claim);
+        migrated += 1;
+    }
+
+    Ok(migrated)
+}
+
+fn round_bid_time(
+    storage: &dyn Storage,
+    round_id: u128,
+) -> StdResult<Option<Timestamp>> {
+    if let Some(round) = ROUNDS.may_load(storage, round_id)? {
+        return Ok(Some(round.bid_time));
+    }
+    if let Some(round) = LIVE_ROUND.may_load(storage)? {
+        if round.id.u128() == round_id {
+            return Ok(Some(round.bid_time));
+        }
+    }
+    if let Some(round) = NEXT_ROUND.may_load(storage)? {
+        if round.id.u128() == round_id {
+            return Ok(Some(round.bid_time));
+        }
+    }
+
+    Ok(None)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -71,17 +400,68 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    /* Bets and payouts wait until an upgrade has rebuilt their state */
+    match msg {
+        ExecuteMsg::MigrateLegacy { .. }
+        | ExecuteMsg::OraclePriceUpdate { .. }
+        | ExecuteMsg::UpdateConfig { .. }
+        | ExecuteMsg::Hault {}
+        | ExecuteMsg::Resume {} => {}
+        _ => assert_migrated(deps.storage)?,
+    }
+
     match msg {
         ExecuteMsg::UpdateConfig { config } => {
             execute_update_config(deps, info, env, config)
         }
-        ExecuteMsg::BetBear { round_id } => {
-            execute_bet(deps, info, env, round_id, Direction::Bear)
-        }
-        ExecuteMsg::BetBull { round_id } => {
-            execute_bet(deps, info, env, round_id, Direction::Bull)
-        }
+        ExecuteMsg::BetBear {
+            round_id,
+            referrer,
+            min_multiplier,
+            conditional,
+        } => execute_bet(
+            deps,
+            info,
+            env,
+            round_id,
+            Direction::Bear,
+            BetOptions {
+                referrer,
+                min_multiplier,
+                conditional,
+            },
+        ),
+        ExecuteMsg::BetBull {
+            round_id,
+            referrer,
+            min_multiplier,
+            conditional,
+        } => execute_bet(
+            deps,
+            info,
+            env,
+            round_id,
+            Direction::Bull,
+            BetOptions {
+                referrer,
+                min_multiplier,
+                conditional,
+            },
+        ),
+        ExecuteMsg::CommitBet {
+            round_id,
+            hash,
+            referrer,
+        } => execute_commit_bet(deps, info, env, round_id, hash, referrer),
+        ExecuteMsg::RevealBet {
+            round_id,
+            direction,
+            salt,
+        } => execute_reveal_bet(deps, info, env, round_id, direction, salt),
         ExecuteMsg::CloseRound {} => execute_close_round(deps, env),
+        ExecuteMsg::OraclePriceUpdate { ticker, .. } => {
+            execute_oracle_price_update(deps, env, info, ticker)
+        }
         ExecuteMsg::CollectWinnings { rounds } => execute_collect_winnings(
             deps,
             info,
@@ -90,25 +470,477 @@ pub fn execute(
         ExecuteMsg::Hault {} => execute_update_hault(deps, info, env, true),
         ExecuteMsg::Resume {} => execute_update_hault(deps, info, env, false),
         ExecuteMsg::FundStakers {} => execute_fund_stakers(deps),
+        ExecuteMsg::SweepDust { round_ids } => execute_sweep_dust(
+            deps,
+            round_ids.iter().map(|r| r.u128()).collect(),
+        ),
+        ExecuteMsg::SweepExpired { round_ids } => execute_sweep_expired(
+            deps,
+            round_ids.iter().map(|r| r.u128()).collect(),
+        ),
+        ExecuteMsg::Prune {
+            before_round,
+            start_after,
+            limit,
+        } => execute_prune(
+            deps,
+            before_round.u128(),
+            start_after.map(|r| r.u128()),
+            limit,
+        ),
+        ExecuteMsg::ClaimReferralFees {} => {
+            execute_claim_referral_fees(deps, info)
+        }
+        ExecuteMsg::MigrateLegacy { limit } => {
+            execute_migrate_legacy(deps, limit)
+        }
     }
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(
+    deps: DepsMut,
+    env: Env,
+    msg: SudoMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::ClockEndBlock {} | SudoMsg::Tick {} => sudo_tick(deps, env),
+        SudoMsg::ForceHalt { halted } => {
+            IS_HAULTED.save(deps.storage, &halted)?;
+            Ok(Response::new().add_event(
+                Event::new("forecast_deliverdao")
+                    .add_attribute("force_hault_games", halted.to_string()),
+            ))
+        }
+        SudoMsg::ForceConfig { config: u_config } => {
+            let config = CONFIG.load(deps.storage)?;
+            let config = merge_config(deps.as_ref(), config, u_config)?;
+            CONFIG.save(deps.storage, &config)?;
+            Ok(Response::new().add_event(
+                Event::new("forecast_deliverdao")
+                    .add_attribute("force_config", "true"),
+            ))
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(
+    deps: DepsMut,
+    _env: Env,
+    msg: Reply,
+) -> Result<Response, ContractError> {
+    match (msg.id, msg.result) {
+        (FUND_STAKERS_REPLY_ID, SubMsgResult::Err(error)) => {
+            /* Keep the fee for the next attempt */
+            let amount = FUNDING.load(deps.storage)?;
+            ACCUMULATED_FEE
+                .update(deps.storage, |acc_fee| -> StdResult<_> {
+                    Ok(acc_fee + amount)
+                })?;
+            FUNDING.remove(deps.storage);
+
+            Ok(Response::new().add_event(
+                Event::new("forecast_deliverdao").add_attributes(vec![
+                    ("fund_stakers_failed", amount.to_string()),
+                    ("error", error),
+                ]),
+            ))
+        }
+        _ => Ok(Response::new()),
+    }
+}
+
+/* Runs every block, so a haulted game is skipped rather than failing */
+fn sudo_tick(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    if IS_HAULTED.load(deps.storage)?
+        || LEGACY_MIGRATION.may_load(deps.storage)?.is_some()
+    {
+        return Ok(Response::new());
+    }
+
+    execute_close_round(deps, env)
+}
+
+/* Same as a tick, only for the oracle and ticker the game settles on */
+fn execute_oracle_price_update(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    ticker: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.fast_oracle_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+    if ticker != oracle_ticker(&config) {
+        return Ok(Response::new());
+    }
+
+    sudo_tick(deps, env)
+}
+
+fn execute_claim_referral_fees(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut referral = REFERRALS
+        .may_load(deps.storage, info.sender.clone())?
+        .unwrap_or_default();
+
+    if referral.unclaimed.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "Nothing to claim",
+        )));
+    }
+
+    let claimed = referral.unclaimed;
+    referral.unclaimed = Uint128::zero();
+    REFERRALS.save(deps.storage, info.sender.clone(), &referral)?;
+    update_liabilities(deps.storage, |liabilities| {
+        liabilities.referral_fees =
+            liabilities.referral_fees.checked_sub(claimed)?;
+        Ok(())
+    })?;
+
+    let msg_send_fees = BankMsg::Send {
+        to_address: info.sender.to_string(),
+        amount: vec![Coin {
+            amount: claimed,
+            denom: SETTLE_DENOM.load(deps.storage)?,
+        }],
+    };
+
+    Ok(Response::new().add_message(msg_send_fees).add_event(
+        Event::new("forecast_deliverdao").add_attributes(vec![
+            ("claimed_referral_fees", claimed.to_string()),
+            ("account", info.sender.to_string()),
+        ]),
+    ))
+}
+
+fn execute_sweep_dust(
+    deps: DepsMut,
+    round_ids: Vec<u128>,
+) -> Result<Response, ContractError> {
+    let settle_denom = SETTLE_DENOM.load(deps.storage)?;
+    let mut swept = Uint128::zero();
+    let mut resp = Response::new();
+
+    let no_duplicate_rounds: HashSet<u128> =
+        HashSet::from_iter(round_ids.iter().cloned());
+
+    for round_id in no_duplicate_rounds {
+        let round = ROUNDS.load(deps.storage, round_id)?;
+        let mut payouts = ROUND_PAYOUTS
+            .may_load(deps.storage, round_id)?
+            .unwrap_or_default();
+
+        let dust = match payout::dust(&round, &payouts) {
+            Some(dust) if !dust.is_zero() => dust,
+            _ => continue,
+        };
+
+        payouts.dust_swept += dust;
+        ROUND_PAYOUTS.save(deps.storage, round_id, &payouts)?;
+        swept += dust;
+
+        resp = resp.add_event(
+            Event::new("forecast_deliverdao").add_attributes(vec![
+                ("round", round_id.to_string()),
+                (
+                    "dust_swept",
+                    Coin::new(dust.u128(), settle_denom.to_string())
+                        .to_string(),
+                ),
+            ]),
+        );
+    }
+
+    if swept.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "No dust to sweep",
+        )));
+    }
+
+    update_liabilities(deps.storage, |liabilities| {
+        liabilities.bets = liabilities.bets.checked_sub(swept)?;
+        Ok(())
+    })?;
+    ACCUMULATED_FEE.update(
+        deps.storage,
+        |fee_before| -> Result<u128, StdError> {
+            Ok(fee_before + swept.u128())
+        },
+    )?;
+
+    Ok(resp)
+}
+
+fn execute_sweep_expired(
+    deps: DepsMut,
+    round_ids: Vec<u128>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let settle_denom = SETTLE_DENOM.load(deps.storage)?;
+    let mut swept = Uint128::zero();
+    let mut resp = Response::new();
+
+    let no_duplicate_rounds: HashSet<u128> =
+        HashSet::from_iter(round_ids.iter().cloned());
+
+    for round_id in no_duplicate_rounds {
+        let round = ROUNDS.load(deps.storage, round_id)?;
+        if !is_expired(deps.storage, &config, round_id) {
+            return Err(ContractError::Std(StdError::generic_err(format!(
+                "Round {} has not expired",
+                round_id
+            ))));
+        }
+
+        let mut payouts = ROUND_PAYOUTS
+            .may_load(deps.storage, round_id)?
+            .unwrap_or_default();
+        /* The bets are left for Prune, which removes them in batches */
+        let unclaimed = payout::unclaimed(&round, &payouts);
+        if unclaimed.is_zero() {
+            continue;
+        }
+
+        payouts.unclaimed_swept += unclaimed;
+        ROUND_PAYOUTS.save(deps.storage, round_id, &payouts)?;
+        swept += unclaimed;
+
+        resp = resp.add_event(
+            Event::new("forecast_deliverdao").add_attributes(vec![
+                ("round", round_id.to_string()),
+                (
+                    "expired_swept",
+                    Coin::new(unclaimed.u128(), settle_denom.to_string())
+                        .to_string(),
+                ),
+            ]),
+        );
+    }
+
+    if resp.events.is_empty() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "Nothing to sweep",
+        )));
+    }
+
+    update_liabilities(deps.storage, |liabilities| {
+        liabilities.bets = liabilities.bets.checked_sub(swept)?;
+        Ok(())
+    })?;
+    ACCUMULATED_FEE.update(
+        deps.storage,
+        |fee_before| -> Result<u128, StdError> {
+            Ok(fee_before + swept.u128())
+        },
+    )?;
+
+    Ok(resp)
+}
+
+fn execute_prune(
+    deps: DepsMut,
+    before_round: u128,
+    start_after: Option<u128>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    /* Every round archived and entry removed uses up one unit */
+    let mut budget =
+        limit.unwrap_or(DEFAULT_PRUNE_LIMIT).min(MAX_PRUNE_LIMIT) as usize;
+    let mut resp = Response::new();
+    /* Where the next Prune can start, every round up to it was handled */
+    let mut done_until = None;
+
+    let rounds: Vec<(u128, FinishedRound)> = ROUNDS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            Some(Bound::exclusive(before_round)),
+            Order::Ascending,
+        )
+        .take(MAX_PRUNE_SCAN)
+        .collect::<StdResult<_>>()?;
+    if rounds.is_empty() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "Nothing to prune",
+        )));
+    }
+
+    for (round_id, round) in rounds {
+        if budget == 0 {
+            break;
+        }
+
+        /* Keep rounds that still owe winnings, refunds or dust */
+        let payouts = ROUND_PAYOUTS
+            .may_load(deps.storage, round_id)?
+            .unwrap_or_default();
+        if !payout::unclaimed(&round, &payouts).is_zero() {
+            done_until = Some(round_id);
+            continue;
+        }
+        budget -= 1;
+
+        let pruned_entries = prune_bets(deps.storage, round_id, budget)?;
+        budget -= pruned_entries;
+
+        /* Out of budget, the next Prune picks up where this one stopped */
+        if has_entries(deps.storage, round_id) {
+            resp = resp.add_event(
+                Event::new("forecast_deliverdao").add_attributes(vec![
+                    ("round", round_id.to_string()),
+                    ("pruned_entries", pruned_entries.to_string()),
+                ]),
+            );
+            break;
+        }
+
+        ROUNDS.remove(deps.storage, round_id);
+        ROUND_PAYOUTS.remove(deps.storage, round_id);
+        ARCHIVED_ROUNDS.save(deps.storage, round_id, &round.into())?;
+        done_until = Some(round_id);
+        resp = resp.add_event(
+            Event::new("forecast_deliverdao").add_attributes(vec![
+                ("round_archived", round_id.to_string()),
+                ("pruned_entries", pruned_entries.to_string()),
+            ]),
+        );
+    }
+
+    /* Rounds that were only kept still move the cursor along */
+    if let Some(round_id) = done_until {
+        resp = resp.add_attribute("prune_start_after", round_id.to_string());
+    }
+    Ok(resp)
+}
+
+/* Remove up to `limit` bets of a round */
+fn prune_bets(
+    storage: &mut dyn Storage,
+    round_id: u128,
+    limit: usize,
+) -> StdResult<usize> {
+    let bettors: Vec<Addr> = BETS
+        .prefix(round_id)
+        .keys(storage, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    for addr in &bettors {
+        BETS.remove(storage, (round_id, addr.clone()));
+    }
+
+    Ok(bettors.len())
+}
+
+fn has_entries(storage: &dyn Storage, round_id: u128) -> bool {
+    BETS.prefix(round_id)
+        .keys(storage, None, None, Order::Ascending)
+        .next()
+        .is_some()
+}
+
+/* Finished rounds stay in ROUNDS until Prune archives them */
+fn is_finished(storage: &dyn Storage, round_id: u128) -> bool {
+    ROUNDS.has(storage, round_id) || ARCHIVED_ROUNDS.has(storage, round_id)
+}
+
+/* A round expires once `claim_expiry_rounds` later rounds have finished */
+fn is_expired(storage: &dyn Storage, config: &Config, round_id: u128) -> bool {
+    !config.claim_expiry_rounds.is_zero()
+        && is_finished(storage, round_id + config.claim_expiry_rounds.u128())
+}
+
 fn execute_fund_stakers(deps: DepsMut) -> Result<Response, ContractError> {
+    let (msgs, event) = fund_stakers(deps)?;
+
+    Ok(Response::new().add_messages(msgs).add_event(event))
+}
+
+/*
+ * Send the accumulated staker fee to the rewards contract, or report why
+ * nothing was sent when the fee has not reached the configured threshold
+ */
+fn fund_stakers(deps: DepsMut) -> StdResult<(Vec<CosmosMsg>, Event)> {
     let config = CONFIG.load(deps.storage)?;
-    let acc_fee = ACCUMULATED_FEE.load(deps.storage)?;
-    let msg = Cw20StakeExternalRewardsExecuteMsg::Fund {};
+    let acc_fee = Uint128::from(ACCUMULATED_FEE.load(deps.storage)?);
+
+    if acc_fee.is_zero() || acc_fee < config.fund_stakers_threshold {
+        return Ok((
+            vec![],
+            Event::new("forecast_deliverdao").add_attributes(vec![
+                ("fund_stakers_skipped", acc_fee.to_string()),
+                (
+                    "fund_stakers_threshold",
+                    config.fund_stakers_threshold.to_string(),
+                ),
+            ]),
+        ));
+    }
+
+    let sink = reward_sink(&config);
     let denom = SETTLE_DENOM.load(deps.storage)?;
 
     ACCUMULATED_FEE.save(deps.storage, &0u128)?;
 
-    Ok(Response::new().add_message(CosmosMsg::Wasm(wasm_execute(
-        config.cw20_stake_external_rewards_addr,
-        &msg,
-        vec![Coin {
-            amount: Uint128::from(acc_fee),
-            denom,
-        }],
-    )?)))
+    Ok((
+        vec![build_fund_msg(
+            &sink,
+            Coin {
+                amount: acc_fee,
+                denom,
+            },
+        )?],
+        Event::new("forecast_deliverdao").add_attributes(vec![
+            ("fund_stakers", acc_fee.to_string()),
+            ("fund_stakers_sink", sink.addr().to_string()),
+        ]),
+    ))
+}
+
+fn reward_sink(config: &Config) -> RewardSink {
+    config
+        .reward_sink
+        .clone()
+        .unwrap_or(RewardSink::StakeCw20ExternalRewards {
+            addr: config.cw20_stake_external_rewards_addr.clone(),
+        })
+}
+
+/*
+ * Each sink type is funded differently, so the message is built here
+ * rather than stored in config
+ */
+fn build_fund_msg(sink: &RewardSink, funds: Coin) -> StdResult<CosmosMsg> {
+    let msg = match sink {
+        RewardSink::StakeCw20ExternalRewards { addr } => {
+            CosmosMsg::Wasm(wasm_execute(
+                addr,
+                &Cw20StakeExternalRewardsExecuteMsg::Fund {},
+                vec![funds],
+            )?)
+        }
+        RewardSink::Cw20Stake { addr, fund_msg } => {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: addr.to_string(),
+                msg: fund_msg.clone(),
+                funds: vec![funds],
+            })
+        }
+        RewardSink::DaoTreasury { addr } | RewardSink::Address { addr } => {
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: addr.to_string(),
+                amount: vec![funds],
+            })
+        }
+    };
+
+    Ok(msg)
 }
 
 fn execute_collect_winnings(
@@ -116,6 +948,7 @@ fn execute_collect_winnings(
     info: MessageInfo,
     rounds: Vec<u128>,
 ) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
     let settle_denom = SETTLE_DENOM.load(deps.storage)?;
     let mut winnings = Uint128::zero();
     let mut resp = Response::new();
@@ -125,76 +958,60 @@ fn execute_collect_winnings(
 
     for round_id in no_duplicate_rounds {
         let round = ROUNDS.load(deps.storage, round_id)?;
+        if is_expired(deps.storage, &config, round_id) {
+            return Err(ContractError::Std(StdError::generic_err(format!(
+                "Round {} has expired",
+                round_id
+            ))));
+        }
         let bet_key = (round_id, info.sender.clone());
 
-        let maybe_bull = BULL_BETS.may_load(deps.storage, bet_key.clone())?;
-        let maybe_bear = BEAR_BETS.may_load(deps.storage, bet_key.clone())?;
-
-        let pool_shares = round.bear_amount + round.bull_amount;
+        /* Only claimable once */
+        let mut bet = match BETS.may_load(deps.storage, bet_key.clone())? {
+            Some(bet) if !bet.claimed => bet,
+            _ => continue,
+        };
 
-        if round.bear_amount == Uint128::zero()
-            || round.bull_amount == Uint128::zero()
-        {
-            // If no both sides are not taken, return funds, only claimable once
-            BULL_BETS.remove(deps.storage, bet_key.clone());
-            BEAR_BETS.remove(deps.storage, bet_key.clone());
+        let round_winnings = match bet.direction {
+            Direction::Bull => {
+                payout::round_winnings(&round, bet.amount, Uint128::zero())
+            }
+            Direction::Bear => {
+                payout::round_winnings(&round, Uint128::zero(), bet.amount)
+            }
+        };
 
-            let bull_shares_no_counter_party =
-                Uint128::from(maybe_bull.unwrap_or(0u128));
-            let bear_shares_no_counter_party =
-                Uint128::from(maybe_bear.unwrap_or(0u128));
+        if round_winnings > Uint128::zero() {
+            bet.claimed = true;
+            BETS.save(deps.storage, bet_key, &bet)?;
 
-            winnings +=
-                bull_shares_no_counter_party + bear_shares_no_counter_party;
-        } else {
-            let round_winnings = match round.winner {
-                Some(Direction::Bull) => {
-                    /* Only claimable once */
-                    BULL_BETS.remove(deps.storage, bet_key);
-                    let won_shares = Uint128::from(maybe_bull.unwrap_or(0u128));
-                    pool_shares.multiply_ratio(won_shares, round.bull_amount)
-                }
-                Some(Direction::Bear) => {
-                    /* Only claimable once */
-                    BEAR_BETS.remove(deps.storage, bet_key);
-                    let won_shares = Uint128::from(maybe_bear.unwrap_or(0u128));
-                    pool_shares.multiply_ratio(won_shares, round.bear_amount)
-                }
-                None => {
-                    /* Only claimable once */
-                    BEAR_BETS.remove(deps.storage, bet_key.clone());
-                    BULL_BETS.remove(deps.storage, bet_key);
-
-                    /* Give back what the wallet bet in case of tie */
-                    (maybe_bear.unwrap_or(0u128) + maybe_bull.unwrap_or(0u128))
-                        .into()
-                }
-            };
-
-            if round_winnings > Uint128::zero() {
-                MY_CLAIMED_ROUNDS.save(
-                    deps.storage,
-                    (info.sender.clone(), round_id),
-                    &true,
-                )?;
-                resp = resp.add_event(
-                    Event::new("forecast_deliverdao").add_attributes(vec![
-                        ("round", round_id.to_string()),
-                        (
-                            "collected_winnings",
-                            Coin::new(
-                                round_winnings.u128(),
-                                settle_denom.to_string(),
-                            )
-                            .to_string(),
-                        ),
-                        ("account", info.sender.to_string()),
-                    ]),
-                );
+            /* Track what was paid so the dust and unclaimed share are known */
+            let mut payouts = ROUND_PAYOUTS
+                .may_load(deps.storage, round_id)?
+                .unwrap_or_default();
+            if round.winner.as_ref() == Some(&bet.direction) {
+                payouts.claimed_shares += bet.amount;
             }
-            /* Count it up */
-            winnings += round_winnings;
+            payouts.paid_out += round_winnings;
+            ROUND_PAYOUTS.save(deps.storage, round_id, &payouts)?;
+
+            resp = resp.add_event(
+                Event::new("forecast_deliverdao").add_attributes(vec![
+                    ("round", round_id.to_string()),
+                    (
+                        "collected_winnings",
+                        Coin::new(
+                            round_winnings.u128(),
+                            settle_denom.to_string(),
+                        )
+                        .to_string(),
+                    ),
+                    ("account", info.sender.to_string()),
+                ]),
+            );
         }
+        /* Count it up */
+        winnings += round_winnings;
     }
 
     if winnings == Uint128::zero() {
@@ -203,6 +1020,11 @@ fn execute_collect_winnings(
         )));
     }
 
+    update_liabilities(deps.storage, |liabilities| {
+        liabilities.bets = liabilities.bets.checked_sub(winnings)?;
+        Ok(())
+    })?;
+
     let msg_send_winnings = BankMsg::Send {
         to_address: info.sender.to_string(),
         amount: vec![Coin {
@@ -214,109 +1036,722 @@ fn execute_collect_winnings(
     Ok(resp.add_message(msg_send_winnings))
 }
 
-fn execute_bet(
+/* Fees a bet pays before it reaches the pool */
+struct BetFees {
+    fee_tier: Option<FeeTier>,
+    burn_fee: Uint128,
+    staker_fee: Uint128,
+    late_surcharge: Uint128,
+    net_amount: Uint128,
+}
+
+fn compute_bet_fees(
+    deps: Deps,
+    config: &Config,
+    bettor: Option<&Addr>,
+    gross: Uint128,
+    late: bool,
+) -> StdResult<BetFees> {
+    let fee_tier = match bettor {
+        Some(bettor) => find_fee_tier(deps, config, bettor)?,
+        None => None,
+    };
+    let burn_fee = compute_burn_fee(deps, gross, &fee_tier)?;
+    let staker_fee = compute_staker_fee(deps, gross, &fee_tier)?;
+
+    /* Bets in the snipe window pay extra to the stakers */
+    let late_surcharge = match late {
+        true => config
+            .late_bet_surcharge
+            .checked_multiply_ratio(gross, FEE_PRECISION * 100)
+            .map_err(|e| StdError::generic_err(e.to_string()))?,
+        false => Uint128::zero(),
+    };
+
+    /* Deduct open + burn fee from the gross amount */
+    let net_amount = gross
+        .checked_sub(staker_fee + burn_fee + late_surcharge)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    Ok(BetFees {
+        fee_tier,
+        burn_fee,
+        staker_fee,
+        late_surcharge,
+        net_amount,
+    })
+}
+
+/* What is left of a bet after the burn, staker and referral fees */
+struct ChargedBet {
+    net_amount: Uint128,
+    fee_tier_name: String,
+    resp: Response,
+}
+
+fn charge_bet_fees(
     deps: DepsMut,
+    info: &MessageInfo,
+    config: &Config,
+    round_id: Uint128,
+    gross: Uint128,
+    referrer: Option<String>,
+    late: bool,
+) -> Result<ChargedBet, ContractError> {
+    let mut resp = Response::new();
+    let BetFees {
+        fee_tier,
+        burn_fee,
+        staker_fee,
+        late_surcharge,
+        net_amount,
+    } = compute_bet_fees(
+        deps.as_ref(),
+        config,
+        Some(&info.sender),
+        gross,
+        late,
+    )?;
+
+    if burn_fee > Uint128::zero() {
+        let msg_burn_fee = BankMsg::Send {
+            to_address: config.burn_addr.to_string(),
+            amount: vec![Coin {
+                amount: burn_fee,
+                denom: SETTLE_DENOM.load(deps.storage)?,
+            }],
+        };
+        resp = resp.add_message(msg_burn_fee);
+    }
+
+    let fee_tier_name = fee_tier
+        .map(|tier| tier.name)
+        .unwrap_or_else(|| "none".to_string());
+
+    /* The referrer's cut comes out of the staker fee */
+    let mut referral_fee = Uint128::zero();
+    if let Some(referrer) = referrer {
+        let referrer = deps.api.addr_validate(&referrer)?;
+        if referrer == info.sender {
+            return Err(ContractError::Std(StdError::generic_err(
+                "You cannot refer your own bets",
+            )));
+        }
+
+        referral_fee = compute_referral_fee(deps.as_ref(), staker_fee)?;
+        REFERRALS.update(
+            deps.storage,
+            referrer.clone(),
+            |maybe_referral| -> StdResult<Referral> {
+                let mut referral = maybe_referral.unwrap_or_default();
+                referral.unclaimed += referral_fee;
+                referral.total_earned += referral_fee;
+                referral.volume += gross;
+                referral.bets += 1;
+                Ok(referral)
+            },
+        )?;
+        resp = resp.add_event(
+            Event::new("forecast_deliverdao").add_attributes(vec![
+                ("round", round_id.to_string()),
+                ("referrer", referrer.to_string()),
+                ("referral_fee", referral_fee.to_string()),
+            ]),
+        );
+    }
+
+    if !late_surcharge.is_zero() {
+        resp = resp.add_event(
+            Event::new("forecast_deliverdao").add_attributes(vec![
+                ("round", round_id.to_string()),
+                ("late_surcharge", late_surcharge.to_string()),
+                ("account", info.sender.to_string()),
+            ]),
+        );
+    }
+
+    ACCUMULATED_FEE.update(
+        deps.storage,
+        |fee_before| -> Result<u128, StdError> {
+            let staker_share = staker_fee
+                .checked_sub(referral_fee)
+                .map_err(|e| StdError::generic_err(e.to_string()))?;
+            Ok(fee_before + (staker_share + late_surcharge).u128())
+        },
+    )?;
+    update_liabilities(deps.storage, |liabilities| {
+        liabilities.referral_fees += referral_fee;
+        Ok(())
+    })?;
+
+    Ok(ChargedBet {
+        net_amount,
+        fee_tier_name,
+        resp,
+    })
+}
+
+/* Optional parts of BetBull / BetBear */
+struct BetOptions {
+    referrer: Option<String>,
+    min_multiplier: Option<Decimal>,
+    conditional: bool,
+}
+
+fn execute_bet(
+    mut deps: DepsMut,
     info: MessageInfo,
     env: Env,
     round_id: Uint128,
     dir: Direction,
+    options: BetOptions,
 ) -> Result<Response, ContractError> {
     assert_not_haulted(deps.as_ref())?;
 
+    if options.conditional && options.min_multiplier.is_none() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "A conditional bet needs a min_multiplier",
+        )));
+    }
+
     let mut bet_round = assert_is_current_round(deps.as_ref(), round_id)?;
-    let mut resp = Response::new();
     let gross = assert_only_settle_denom(deps.as_ref(), &info.funds)?;
     let config = CONFIG.load(deps.storage)?;
 
-    if env.block.time > bet_round.open_time {
+    if config.commit_reveal {
+        return Err(ContractError::Std(StdError::generic_err(
+            "Bets must be placed with CommitBet in commit-reveal mode",
+        )));
+    }
+
+    if gross < config.minimum_bet {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "Bets must be at least {}",
+            config.minimum_bet
+        ))));
+    }
+
+    if env.block.time > bet_round.open_time {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "Round {} stopped accepting bids {} second(s) ago; the next round has not yet begun", round_id,
+                (env.block.time.seconds() - bet_round.open_time.seconds())
+        ))));
+    }
+
+    let ChargedBet {
+        net_amount: bet_amt,
+        fee_tier_name,
+        mut resp,
+    } = charge_bet_fees(
+        deps.branch(),
+        &info,
+        &config,
+        round_id,
+        gross,
+        options.referrer,
+        is_late_bet(&config, &bet_round, env.block.time),
+    )?;
+
+    let bet_key = (round_id.u128(), info.sender.clone());
+    assert_no_open_bet(deps.as_ref(), &bet_key)?;
+
+    if options.conditional {
+        if bet_round.conditional_bets >= MAX_CONDITIONAL_BETS {
+            return Err(ContractError::Std(StdError::generic_err(format!(
+                "Round {} already holds {} conditional bets",
+                round_id, MAX_CONDITIONAL_BETS
+            ))));
+        }
+        bet_round.conditional_bets += 1;
+        NEXT_ROUND.save(deps.storage, &bet_round)?;
+
+        let min_multiplier = options.min_multiplier.unwrap_or_default();
+        update_liabilities(deps.storage, |liabilities| {
+            liabilities.escrow += bet_amt;
+            Ok(())
+        })?;
+        CONDITIONAL_BETS.save(
+            deps.storage,
+            bet_key,
+            &ConditionalBet {
+                direction: dir.clone(),
+                amount: bet_amt,
+                min_multiplier,
+            },
+        )?;
+        /* Not counted in the round totals until the round opens */
+        return Ok(resp.add_event(
+            Event::new("forecast_deliverdao").add_attributes(vec![
+                ("round", round_id.to_string()),
+                ("conditional_bet", dir.to_string()),
+                ("amount", bet_amt.to_string()),
+                ("min_multiplier", min_multiplier.to_string()),
+                ("account", info.sender.to_string()),
+                ("fee_tier", fee_tier_name),
+            ]),
+        ));
+    }
+
+    if let Some(min_multiplier) = options.min_multiplier {
+        let (bull_amount, bear_amount) = match dir {
+            Direction::Bull => {
+                (bet_round.bull_amount + bet_amt, bet_round.bear_amount)
+            }
+            Direction::Bear => {
+                (bet_round.bull_amount, bet_round.bear_amount + bet_amt)
+            }
+        };
+        let multiplier = payout::multiplier(bull_amount, bear_amount, &dir);
+        if multiplier < min_multiplier {
+            return Err(ContractError::Std(StdError::generic_err(format!(
+                "Payout multiplier {} is below the minimum {}",
+                multiplier, min_multiplier
+            ))));
+        }
+    }
+
+    update_liabilities(deps.storage, |liabilities| {
+        liabilities.bets += bet_amt;
+        Ok(())
+    })?;
+
+    BETS.save(
+        deps.storage,
+        bet_key,
+        &Bet {
+            direction: dir.clone(),
+            amount: bet_amt,
+            placed_at: env.block.time,
+            claimed: false,
+        },
+    )?;
+
+    let (bull_before, bear_before) =
+        (bet_round.bull_amount, bet_round.bear_amount);
+    match dir {
+        Direction::Bull => {
+            bet_round.bull_amount += bet_amt;
+            resp = resp.add_event(
+                Event::new("forecast_deliverdao").add_attributes(vec![
+                    ("round", round_id.to_string()),
+                    ("bet_bull", bet_amt.to_string()),
+                    ("round_bull_total", bet_round.bull_amount.to_string()),
+                    ("account", info.sender.to_string()),
+                    ("fee_tier", fee_tier_name),
+                ]),
+            );
+        }
+        Direction::Bear => {
+            bet_round.bear_amount += bet_amt;
+            resp = resp.add_event(
+                Event::new("forecast_deliverdao").add_attributes(vec![
+                    ("round", round_id.to_string()),
+                    ("bet_bear", bet_amt.to_string()),
+                    ("round_bear_total", bet_round.bear_amount.to_string()),
+                    ("account", info.sender.to_string()),
+                    ("fee_tier", fee_tier_name),
+                ]),
+            );
+        }
+    }
+
+    if is_late_bet(&config, &bet_round, env.block.time) {
+        if let Some(extension) = extend_sniped_round(
+            &config,
+            &mut bet_round,
+            bull_before,
+            bear_before,
+        ) {
+            resp = resp.add_event(
+                Event::new("forecast_deliverdao").add_attributes(vec![
+                    ("round", round_id.to_string()),
+                    ("bidding_extended", extension.to_string()),
+                    ("open_time", bet_round.open_time.seconds().to_string()),
+                ]),
+            );
+        }
+    }
+    NEXT_ROUND.save(deps.storage, &bet_round)?;
+
+    Ok(resp)
+}
+
+/* Bets in the last `snipe_window_seconds` before the round locks */
+fn is_late_bet(config: &Config, round: &NextRound, now: Timestamp) -> bool {
+    !config.snipe_window_seconds.is_zero()
+        && now.plus_seconds(config.snipe_window_seconds.u128() as u64)
+            >= round.open_time
+}
+
+/* Push back open_time when a late bet swings the pool ratio too much */
+fn extend_sniped_round(
+    config: &Config,
+    round: &mut NextRound,
+    bull_before: Uint128,
+    bear_before: Uint128,
+) -> Option<Uint128> {
+    if config.snipe_extension_seconds.is_zero()
+        || (bull_before + bear_before).is_zero()
+    {
+        return None;
+    }
+
+    let share_before =
+        Decimal::from_ratio(bull_before, bull_before + bear_before);
+    let share_after = Decimal::from_ratio(
+        round.bull_amount,
+        round.bull_amount + round.bear_amount,
+    );
+    let moved = match share_after > share_before {
+        true => share_after - share_before,
+        false => share_before - share_after,
+    };
+    if moved
+        <= Decimal::from_ratio(
+            config.snipe_ratio_threshold,
+            FEE_PRECISION * 100,
+        )
+    {
+        return None;
+    }
+
+    let extension = config.snipe_extension_seconds.min(
+        config
+            .max_snipe_extension_seconds
+            .saturating_sub(round.extended_seconds),
+    );
+    if extension.is_zero() {
+        return None;
+    }
+
+    round.extended_seconds += extension;
+    round.open_time = round.open_time.plus_seconds(extension.u128() as u64);
+    round.close_time = round.close_time.plus_seconds(extension.u128() as u64);
+    Some(extension)
+}
+
+fn execute_commit_bet(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    round_id: Uint128,
+    hash: Binary,
+    referrer: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_not_haulted(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+
+    if !config.commit_reveal {
+        return Err(ContractError::Std(StdError::generic_err(
+            "Commit-reveal mode is not enabled",
+        )));
+    }
+
+    let bet_round = assert_is_current_round(deps.as_ref(), round_id)?;
+    let gross = assert_only_settle_denom(deps.as_ref(), &info.funds)?;
+
+    if env.block.time > bet_round.open_time {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "Round {} stopped accepting bids {} second(s) ago; the next round has not yet begun", round_id,
+                (env.block.time.seconds() - bet_round.open_time.seconds())
+        ))));
+    }
+
+    if hash.len() != 32 {
+        return Err(ContractError::Std(StdError::generic_err(
+            "The commitment must be a sha256 hash",
+        )));
+    }
+
+    if gross < config.minimum_bet {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "Bets must be at least {}",
+            config.minimum_bet
+        ))));
+    }
+
+    let bet_key = (round_id.u128(), info.sender.clone());
+    assert_no_open_bet(deps.as_ref(), &bet_key)?;
+    if COMMITMENTS.has(deps.storage, bet_key.clone()) {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "You already committed to round {}",
+            round_id
+        ))));
+    }
+
+    let ChargedBet {
+        net_amount,
+        fee_tier_name,
+        resp,
+    } = charge_bet_fees(
+        deps.branch(),
+        &info,
+        &config,
+        round_id,
+        gross,
+        referrer,
+        is_late_bet(&config, &bet_round, env.block.time),
+    )?;
+
+    COMMITMENTS.save(
+        deps.storage,
+        bet_key,
+        &Commitment {
+            hash,
+            amount: net_amount,
+            gross_amount: gross,
+            committed_at: env.block.time,
+        },
+    )?;
+    update_liabilities(deps.storage, |liabilities| {
+        liabilities.escrow += net_amount;
+        Ok(())
+    })?;
+
+    Ok(
+        resp.add_event(Event::new("forecast_deliverdao").add_attributes(vec![
+            ("round", round_id.to_string()),
+            ("bet_committed", net_amount.to_string()),
+            ("account", info.sender.to_string()),
+            ("fee_tier", fee_tier_name),
+        ])),
+    )
+}
+
+fn execute_reveal_bet(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    round_id: Uint128,
+    direction: Direction,
+    salt: String,
+) -> Result<Response, ContractError> {
+    assert_not_haulted(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    let bet_key = (round_id.u128(), info.sender.clone());
+
+    let commitment = COMMITMENTS
+        .may_load(deps.storage, bet_key.clone())?
+        .ok_or_else(|| {
+            StdError::generic_err(format!(
+                "You have no commitment in round {}",
+                round_id
+            ))
+        })?;
+
+    if commitment.hash
+        != commitment_hash(round_id, &direction, &salt, &info.sender)
+    {
+        return Err(ContractError::Std(StdError::generic_err(
+            "The revealed bet does not match the commitment",
+        )));
+    }
+
+    /* The config may have changed since the commitment */
+    if commitment.gross_amount < config.minimum_bet {
         return Err(ContractError::Std(StdError::generic_err(format!(
-            "Round {} stopped accepting bids {} second(s) ago; the next round has not yet begun", round_id,
-                (env.block.time.seconds() - bet_round.open_time.seconds())
+            "Bets must be at least {}",
+            config.minimum_bet
         ))));
     }
+    assert_no_open_bet(deps.as_ref(), &bet_key)?;
+
+    /* The round is still the bidding one until CloseRound opens it */
+    let (mut next_round, mut live_round) = (
+        NEXT_ROUND
+            .may_load(deps.storage)?
+            .filter(|round| round.id == round_id),
+        LIVE_ROUND
+            .may_load(deps.storage)?
+            .filter(|round| round.id == round_id),
+    );
+    let open_time = match (&next_round, &live_round) {
+        (Some(round), _) => round.open_time,
+        (_, Some(round)) => round.open_time,
+        _ => {
+            return Err(ContractError::Std(StdError::generic_err(format!(
+                "Round {} is neither bidding nor live",
+                round_id
+            ))))
+        }
+    };
 
-    let burn_fee = compute_burn_fee(deps.as_ref(), gross)?;
+    let now = env.block.time;
+    if now < open_time {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "Bets in round {} can be revealed once it stops accepting bids",
+            round_id
+        ))));
+    }
+    if now >= open_time.plus_seconds(config.reveal_window_seconds.u128() as u64)
+    {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "The reveal window of round {} is over",
+            round_id
+        ))));
+    }
 
-    if burn_fee > Uint128::zero() {
-        let msg_burn_fee = BankMsg::Send {
-            to_address: config.burn_addr.to_string(),
-            amount: vec![Coin {
-                amount: burn_fee,
-                denom: SETTLE_DENOM.load(deps.storage)?,
-            }],
-        };
-        resp = resp.add_message(msg_burn_fee);
+    let amount = commitment.amount;
+    if let Some(round) = next_round.as_mut() {
+        match direction {
+            Direction::Bull => round.bull_amount += amount,
+            Direction::Bear => round.bear_amount += amount,
+        }
+        NEXT_ROUND.save(deps.storage, round)?;
+    }
+    if let Some(round) = live_round.as_mut() {
+        match direction {
+            Direction::Bull => round.bull_amount += amount,
+            Direction::Bear => round.bear_amount += amount,
+        }
+        LIVE_ROUND.save(deps.storage, round)?;
     }
 
-    let staker_fee = compute_staker_fee(deps.as_ref(), gross)?;
-    ACCUMULATED_FEE.update(
+    COMMITMENTS.remove(deps.storage, bet_key.clone());
+    BETS.save(
         deps.storage,
-        |fee_before| -> Result<u128, StdError> {
-            Ok(fee_before + staker_fee.u128())
+        bet_key,
+        &Bet {
+            direction: direction.clone(),
+            amount,
+            placed_at: commitment.committed_at,
+            claimed: false,
         },
     )?;
+    update_liabilities(deps.storage, |liabilities| {
+        liabilities.escrow = liabilities.escrow.checked_sub(amount)?;
+        liabilities.bets += amount;
+        Ok(())
+    })?;
 
-    /* Deduct open + burn fee from the gross amount */
-    let bet_amt = gross - staker_fee - burn_fee;
-
-    let bet_key = (round_id.u128(), info.sender.clone());
-
-    let already_bullish = BULL_BETS
-        .load(deps.storage, bet_key.clone())
-        .unwrap_or(0u128);
-    let already_bearish = BEAR_BETS
-        .load(deps.storage, bet_key.clone())
-        .unwrap_or(0u128);
+    Ok(Response::new().add_event(
+        Event::new("forecast_deliverdao").add_attributes(vec![
+            ("round", round_id.to_string()),
+            ("bet_revealed", direction.to_string()),
+            ("amount", amount.to_string()),
+            ("account", info.sender.to_string()),
+        ]),
+    ))
+}
 
-    /* Refuse to add to an open bet or bet in different directions; prefer to cancel the current bet and re-open */
-    if already_bullish > 0 {
+/*
+ * Refuse to add to an open bet or bet in different directions; prefer to
+ * cancel the current bet and re-open
+ */
+fn assert_no_open_bet(
+    deps: Deps,
+    bet_key: &(u128, Addr),
+) -> Result<(), ContractError> {
+    if let Some(bet) = BETS.may_load(deps.storage, bet_key.clone())? {
         return Err(ContractError::Std(StdError::generic_err(format!(
-            "You are already bullish {}",
-            already_bullish
+            "You are already {} {}",
+            match bet.direction {
+                Direction::Bull => "bullish",
+                Direction::Bear => "bearish",
+            },
+            bet.amount
         ))));
     }
 
-    if already_bearish > 0 {
+    if let Some(conditional_bet) =
+        CONDITIONAL_BETS.may_load(deps.storage, bet_key.clone())?
+    {
         return Err(ContractError::Std(StdError::generic_err(format!(
-            "You are already bearish {}",
-            already_bearish
+            "You already have a conditional {} bet of {}",
+            conditional_bet.direction.to_string(),
+            conditional_bet.amount
         ))));
     }
 
-    match dir {
-        Direction::Bull => {
-            BULL_BETS.save(deps.storage, bet_key, &bet_amt.u128())?;
-            bet_round.bull_amount += bet_amt;
-            NEXT_ROUND.save(deps.storage, &bet_round)?;
-            resp = resp.add_event(
-                Event::new("forecast_deliverdao").add_attributes(vec![
-                    ("round", round_id.to_string()),
-                    ("bet_bull", bet_amt.to_string()),
-                    ("round_bull_total", bet_round.bull_amount.to_string()),
-                    ("account", info.sender.to_string()),
-                ]),
-            );
-        }
-        Direction::Bear => {
-            BEAR_BETS.save(deps.storage, bet_key, &bet_amt.u128())?;
-            bet_round.bear_amount += bet_amt;
-            NEXT_ROUND.save(deps.storage, &bet_round)?;
-            resp = resp.add_event(
-                Event::new("forecast_deliverdao").add_attributes(vec![
-                    ("round", round_id.to_string()),
-                    ("bet_bear", bet_amt.to_string()),
-                    ("round_bear_total", bet_round.bear_amount.to_string()),
-                    ("account", info.sender.to_string()),
-                ]),
-            );
+    Ok(())
+}
+
+/* The hash a CommitBet has to carry for the given bet */
+pub fn commitment_hash(
+    round_id: Uint128,
+    direction: &Direction,
+    salt: &str,
+    sender: &Addr,
+) -> Binary {
+    let preimage =
+        format!("{}:{}:{}:{}", round_id, direction.to_string(), salt, sender);
+    Binary::from(Sha256::digest(preimage.as_bytes()).as_slice())
+}
+
+/*
+ * Forfeit or refund, per config, the commitments nobody revealed in rounds
+ * up to `last_round`, always refunding them in voided rounds. Bounded, so
+ * leftovers of a crowded round are settled by the following closes.
+ */
+fn settle_unrevealed_commitments(
+    deps: DepsMut,
+    forfeit_unrevealed: bool,
+    last_round: u128,
+) -> StdResult<(Vec<CosmosMsg>, Vec<Event>)> {
+    let end = Bound::exclusive((last_round + 1, Addr::unchecked("")));
+    let commitments: Vec<((u128, Addr), Commitment)> = COMMITMENTS
+        .range(deps.storage, None, Some(end), Order::Ascending)
+        .take(MAX_SETTLED_COMMITMENTS)
+        .collect::<StdResult<_>>()?;
+
+    let settle_denom = SETTLE_DENOM.load(deps.storage)?;
+    let mut refunds = vec![];
+    let mut events = vec![];
+    for ((round_id, addr), commitment) in commitments {
+        let forfeit = forfeit_unrevealed && !is_voided(deps.storage, round_id)?;
+        COMMITMENTS.remove(deps.storage, (round_id, addr.clone()));
+        update_liabilities(deps.storage, |liabilities| {
+            liabilities.escrow =
+                liabilities.escrow.checked_sub(commitment.amount)?;
+            Ok(())
+        })?;
+
+        if forfeit {
+            ACCUMULATED_FEE.update(
+                deps.storage,
+                |fee_before| -> Result<u128, StdError> {
+                    Ok(fee_before + commitment.amount.u128())
+                },
+            )?;
+        } else {
+            refunds.push(CosmosMsg::Bank(BankMsg::Send {
+                to_address: addr.to_string(),
+                amount: vec![Coin {
+                    amount: commitment.amount,
+                    denom: settle_denom.clone(),
+                }],
+            }));
         }
+        events.push(Event::new("forecast_deliverdao").add_attributes(vec![
+            ("round", round_id.to_string()),
+            (
+                if forfeit {
+                    "commitment_forfeited"
+                } else {
+                    "commitment_refunded"
+                },
+                commitment.amount.to_string(),
+            ),
+            ("account", addr.to_string()),
+        ]));
     }
 
-    Ok(resp)
+    Ok((refunds, events))
+}
+
+/* Finished rounds may already have been archived by Prune */
+fn is_voided(storage: &dyn Storage, round_id: u128) -> StdResult<bool> {
+    if let Some(round) = ROUNDS.may_load(storage, round_id)? {
+        return Ok(round.voided);
+    }
+    Ok(ARCHIVED_ROUNDS
+        .may_load(storage, round_id)?
+        .map(|round| round.voided)
+        .unwrap_or_default())
 }
 
 fn execute_close_round(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
 ) -> Result<Response, ContractError> {
     assert_not_haulted(deps.as_ref())?;
@@ -332,7 +1767,7 @@ fn execute_close_round(
         Some(live_round) => {
             if now >= live_round.close_time {
                 let finished_round =
-                    compute_round_close(deps.as_ref(), live_round)?;
+                    compute_round_close(deps.as_ref(), &env, live_round)?;
                 ROUNDS.save(
                     deps.storage,
                     live_round.id.u128(),
@@ -342,6 +1777,7 @@ fn execute_close_round(
                     Event::new("forecast_deliverdao").add_attributes(vec![
                         ("round_dead", live_round.id.to_string()),
                         ("close_price", finished_round.close_price.to_string()),
+                        ("voided", finished_round.voided.to_string()),
                         (
                             "winner",
                             match finished_round.winner {
@@ -352,11 +1788,33 @@ fn execute_close_round(
                     ]),
                 );
                 LIVE_ROUND.remove(deps.storage);
+
+                let (refunds, events) = settle_unrevealed_commitments(
+                    deps.branch(),
+                    config.forfeit_unrevealed,
+                    live_round.id.u128(),
+                )?;
+                resp = resp.add_messages(refunds).add_events(events);
             }
         }
         None => {}
     }
 
+    if config.auto_fund_stakers {
+        let acc_fee = ACCUMULATED_FEE.load(deps.storage)?;
+        let (msgs, event) = fund_stakers(deps.branch())?;
+        /* Only report automatic funding when something was sent */
+        if !msgs.is_empty() {
+            /* A failing sink must not stop the rounds */
+            FUNDING.save(deps.storage, &acc_fee)?;
+            resp = resp
+                .add_submessages(msgs.into_iter().map(|msg| {
+                    SubMsg::reply_on_error(msg, FUND_STAKERS_REPLY_ID)
+                }))
+                .add_event(event);
+        }
+    }
+
     /* Close the bidding round if it is finished
      * NOTE Don't allow two live rounds at the same time - wait for the other to close
      */
@@ -377,6 +1835,8 @@ fn execute_close_round(
             &NextRound {
                 bear_amount: Uint128::zero(),
                 bull_amount: Uint128::zero(),
+                extended_seconds: Uint128::zero(),
+                conditional_bets: 0,
                 bid_time: env.block.time,
                 close_time,
                 open_time,
@@ -392,8 +1852,53 @@ fn execute_close_round(
             if LIVE_ROUND.may_load(deps.storage)?.is_none()
                 && now >= open_round.open_time
             {
-                let live_round =
-                    compute_round_open(deps.as_ref(), env.clone(), open_round)?;
+                let mut open_round = open_round.clone();
+                let (refunds, events) = settle_conditional_bets(
+                    deps.branch(),
+                    &mut open_round,
+                    now,
+                )?;
+                resp = resp.add_messages(refunds).add_events(events);
+                let live_round = match compute_round_open(
+                    deps.as_ref(),
+                    env.clone(),
+                    &open_round,
+                )? {
+                    Some(live_round) => live_round,
+                    None => {
+                        /* Nothing to open on, refund the round right away */
+                        let voided_round = void_round(&open_round);
+                        ROUNDS.save(
+                            deps.storage,
+                            open_round.id.u128(),
+                            &voided_round,
+                        )?;
+                        let (refunds, events) = settle_unrevealed_commitments(
+                            deps.branch(),
+                            config.forfeit_unrevealed,
+                            open_round.id.u128(),
+                        )?;
+                        resp = resp
+                            .add_messages(refunds)
+                            .add_events(events)
+                            .add_event(
+                                Event::new("forecast_deliverdao")
+                                    .add_attribute(
+                                        "round_voided",
+                                        open_round.id,
+                                    ),
+                            );
+                        NEXT_ROUND.remove(deps.storage);
+                        let new_round_id = new_bid_round(deps, env)?;
+                        resp = resp.add_event(
+                            Event::new("forecast_deliverdao").add_attribute(
+                                "round_bidding_open",
+                                new_round_id,
+                            ),
+                        );
+                        return Ok(resp);
+                    }
+                };
                 resp = resp.add_event(
                     Event::new("forecast_deliverdao").add_attributes(vec![
                         ("round_bidding_close", live_round.id),
@@ -423,6 +1928,158 @@ fn execute_close_round(
     Ok(resp)
 }
 
+/*
+ * Count the escrowed bets whose minimum multiplier still holds at the final
+ * odds and refund the rest (net of fees). The most demanding failing bet is
+ * dropped first, since dropping it can improve the odds of the others.
+ */
+fn settle_conditional_bets(
+    deps: DepsMut,
+    round: &mut NextRound,
+    now: Timestamp,
+) -> StdResult<(Vec<CosmosMsg>, Vec<Event>)> {
+    let round_id = round.id.u128();
+    let conditional_bets: Vec<(Addr, ConditionalBet)> = CONDITIONAL_BETS
+        .prefix(round_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+
+    /*
+     * Each side most demanding first: if any bet of a side fails, its first
+     * remaining one does, so one pass over the sorted sides is enough
+     */
+    let (mut bull_amount, mut bear_amount) =
+        (round.bull_amount, round.bear_amount);
+    let (mut bulls, mut bears) = (vec![], vec![]);
+    for (i, (_, bet)) in conditional_bets.iter().enumerate() {
+        match bet.direction {
+            Direction::Bull => {
+                bull_amount += bet.amount;
+                bulls.push(i);
+            }
+            Direction::Bear => {
+                bear_amount += bet.amount;
+                bears.push(i);
+            }
+        }
+    }
+    for side in [&mut bulls, &mut bears] {
+        side.sort_by(|a, b| {
+            conditional_bets[*b]
+                .1
+                .min_multiplier
+                .cmp(&conditional_bets[*a].1.min_multiplier)
+        });
+    }
+
+    let mut accepted = vec![true; conditional_bets.len()];
+    let (mut next_bull, mut next_bear) = (0, 0);
+    loop {
+        let failing = |side: &[usize], next: usize| {
+            side.get(next)
+                .map(|i| &conditional_bets[*i].1)
+                .filter(|bet| {
+                    payout::multiplier(bull_amount, bear_amount, &bet.direction)
+                        < bet.min_multiplier
+                })
+                .map(|bet| bet.min_multiplier)
+        };
+
+        /* Drop the most demanding failing bet, it may fix the others */
+        let dropped =
+            match (failing(&bulls, next_bull), failing(&bears, next_bear)) {
+                (Some(bull), Some(bear)) if bull >= bear => {
+                    next_bull += 1;
+                    bulls[next_bull - 1]
+                }
+                (_, Some(_)) => {
+                    next_bear += 1;
+                    bears[next_bear - 1]
+                }
+                (Some(_), None) => {
+                    next_bull += 1;
+                    bulls[next_bull - 1]
+                }
+                (None, None) => break,
+            };
+
+        accepted[dropped] = false;
+        let bet = &conditional_bets[dropped].1;
+        match bet.direction {
+            Direction::Bull => bull_amount -= bet.amount,
+            Direction::Bear => bear_amount -= bet.amount,
+        }
+    }
+
+    let settle_denom = SETTLE_DENOM.load(deps.storage)?;
+    let mut refunds = vec![];
+    let mut events = vec![];
+    for (i, (addr, bet)) in conditional_bets.into_iter().enumerate() {
+        let bet_key = (round_id, addr.clone());
+        CONDITIONAL_BETS.remove(deps.storage, bet_key.clone());
+        update_liabilities(deps.storage, |liabilities| {
+            liabilities.escrow = liabilities.escrow.checked_sub(bet.amount)?;
+            if accepted[i] {
+                liabilities.bets += bet.amount;
+            }
+            Ok(())
+        })?;
+
+        if accepted[i] {
+            BETS.save(
+                deps.storage,
+                bet_key,
+                &Bet {
+                    direction: bet.direction.clone(),
+                    amount: bet.amount,
+                    placed_at: now,
+                    claimed: false,
+                },
+            )?;
+            match bet.direction {
+                Direction::Bull => round.bull_amount += bet.amount,
+                Direction::Bear => round.bear_amount += bet.amount,
+            }
+            events.push(Event::new("forecast_deliverdao").add_attributes(
+                vec![
+                    ("round", round.id.to_string()),
+                    ("conditional_bet_accepted", bet.direction.to_string()),
+                    ("amount", bet.amount.to_string()),
+                    ("account", addr.to_string()),
+                ],
+            ));
+        } else {
+            refunds.push(CosmosMsg::Bank(BankMsg::Send {
+                to_address: addr.to_string(),
+                amount: vec![Coin {
+                    amount: bet.amount,
+                    denom: settle_denom.clone(),
+                }],
+            }));
+            events.push(Event::new("forecast_deliverdao").add_attributes(
+                vec![
+                    ("round", round.id.to_string()),
+                    ("conditional_bet_refunded", bet.direction.to_string()),
+                    ("amount", bet.amount.to_string()),
+                    ("account", addr.to_string()),
+                ],
+            ));
+        }
+    }
+
+    Ok((refunds, events))
+}
+
+/* An underflow means the accounting drifted, so it fails loudly */
+fn update_liabilities<F>(storage: &mut dyn Storage, action: F) -> StdResult<()>
+where
+    F: FnOnce(&mut Liabilities) -> StdResult<()>,
+{
+    let mut liabilities = LIABILITIES.may_load(storage)?.unwrap_or_default();
+    action(&mut liabilities)?;
+    LIABILITIES.save(storage, &liabilities)
+}
+
 fn execute_update_config(
     deps: DepsMut,
     info: MessageInfo,
@@ -431,7 +2088,18 @@ fn execute_update_config(
 ) -> Result<Response, ContractError> {
     assert_is_admin(deps.as_ref(), info, env)?;
     let config = CONFIG.load(deps.as_ref().storage)?;
+    let config = merge_config(deps.as_ref(), config, u_config)?;
+    CONFIG.save(deps.storage, &config)?;
 
+    Ok(Response::new())
+}
+
+/* Apply the fields set in `u_config` on top of `config` */
+fn merge_config(
+    deps: Deps,
+    config: Config,
+    u_config: PartialConfig,
+) -> StdResult<Config> {
     let next_round_seconds = u_config
         .next_round_seconds
         .unwrap_or(config.next_round_seconds);
@@ -444,25 +2112,141 @@ fn execute_update_config(
     let burn_fee = u_config.burn_fee.unwrap_or(config.burn_fee);
     let burn_addr = u_config.burn_addr.unwrap_or(config.burn_addr);
     let staker_fee = u_config.staker_fee.unwrap_or(config.staker_fee);
+    let fund_stakers_threshold = u_config
+        .fund_stakers_threshold
+        .unwrap_or(config.fund_stakers_threshold);
+    let auto_fund_stakers = u_config
+        .auto_fund_stakers
+        .unwrap_or(config.auto_fund_stakers);
+    let reward_sink = u_config.reward_sink.unwrap_or(config.reward_sink);
+    let referral_share =
+        u_config.referral_share.unwrap_or(config.referral_share);
+    let fee_tiers = u_config.fee_tiers.unwrap_or(config.fee_tiers);
+    let fee_tier_whitelist = u_config
+        .fee_tier_whitelist
+        .unwrap_or(config.fee_tier_whitelist);
+    let fee_tier_staking_addr = u_config
+        .fee_tier_staking_addr
+        .unwrap_or(config.fee_tier_staking_addr);
+    let claim_expiry_rounds = u_config
+        .claim_expiry_rounds
+        .unwrap_or(config.claim_expiry_rounds);
+    let commit_reveal = u_config.commit_reveal.unwrap_or(config.commit_reveal);
+    let reveal_window_seconds = u_config
+        .reveal_window_seconds
+        .unwrap_or(config.reveal_window_seconds);
+    let forfeit_unrevealed = u_config
+        .forfeit_unrevealed
+        .unwrap_or(config.forfeit_unrevealed);
+    let snipe_window_seconds = u_config
+        .snipe_window_seconds
+        .unwrap_or(config.snipe_window_seconds);
+    let snipe_ratio_threshold = u_config
+        .snipe_ratio_threshold
+        .unwrap_or(config.snipe_ratio_threshold);
+    let snipe_extension_seconds = u_config
+        .snipe_extension_seconds
+        .unwrap_or(config.snipe_extension_seconds);
+    let max_snipe_extension_seconds = u_config
+        .max_snipe_extension_seconds
+        .unwrap_or(config.max_snipe_extension_seconds);
+    let late_bet_surcharge = u_config
+        .late_bet_surcharge
+        .unwrap_or(config.late_bet_surcharge);
+    let ticker = u_config.ticker.unwrap_or(config.ticker);
+    let settle_at_round_times = u_config
+        .settle_at_round_times
+        .unwrap_or(config.settle_at_round_times);
+    let price_tolerance_seconds = u_config
+        .price_tolerance_seconds
+        .unwrap_or(config.price_tolerance_seconds);
+    let twap_window_seconds = u_config
+        .twap_window_seconds
+        .unwrap_or(config.twap_window_seconds);
+    if ticker.as_deref() == Some("") {
+        return Err(StdError::generic_err("The ticker cannot be empty"));
+    }
+    if let Some(staking_addr) = &fee_tier_staking_addr {
+        deps.api.addr_validate(staking_addr.as_ref())?;
+    }
+    if let Some(sink) = &reward_sink {
+        deps.api.addr_validate(sink.addr().as_ref())?;
+    }
 
-    CONFIG.save(
-        deps.storage,
-        &Config {
-            next_round_seconds,
-            fast_oracle_addr,
-            cw20_stake_external_rewards_addr,
-            minimum_bet,
-            burn_addr,
-            burn_fee,
-            staker_fee,
-        },
-    )?;
+    let config = Config {
+        next_round_seconds,
+        fast_oracle_addr,
+        cw20_stake_external_rewards_addr,
+        minimum_bet,
+        burn_addr,
+        burn_fee,
+        staker_fee,
+        fund_stakers_threshold,
+        auto_fund_stakers,
+        reward_sink,
+        referral_share,
+        fee_tiers,
+        fee_tier_whitelist,
+        fee_tier_staking_addr,
+        claim_expiry_rounds,
+        commit_reveal,
+        reveal_window_seconds,
+        forfeit_unrevealed,
+        snipe_window_seconds,
+        snipe_ratio_threshold,
+        snipe_extension_seconds,
+        max_snipe_extension_seconds,
+        late_bet_surcharge,
+        ticker,
+        settle_at_round_times,
+        price_tolerance_seconds,
+        twap_window_seconds,
+    };
+    validate_config(&config)?;
 
-    Ok(Response::new())
+    Ok(config)
+}
+
+/* Rates are percentages scaled by FEE_PRECISION */
+fn validate_config(config: &Config) -> StdResult<()> {
+    let max_rate = Uint128::new(FEE_PRECISION * 100);
+
+    if config.referral_share > max_rate {
+        return Err(StdError::generic_err(
+            "The referral share cannot exceed 100%",
+        ));
+    }
+    if config.burn_fee + config.staker_fee > max_rate {
+        return Err(StdError::generic_err("Fees cannot exceed 100%"));
+    }
+    if !config.fee_tiers.is_empty() && config.fee_tier_staking_addr.is_none() {
+        return Err(StdError::generic_err(
+            "Fee tiers need a staking contract to check balances against",
+        ));
+    }
+    for tier in &config.fee_tiers {
+        if tier.burn_fee + tier.staker_fee > max_rate {
+            return Err(StdError::generic_err(format!(
+                "Fees of tier {} cannot exceed 100%",
+                tier.name
+            )));
+        }
+    }
+    /* Reveals have to be counted before the live round closes */
+    if config.commit_reveal
+        && (config.reveal_window_seconds.is_zero()
+            || config.reveal_window_seconds >= config.next_round_seconds)
+    {
+        return Err(StdError::generic_err(
+            "The reveal window must be shorter than a round",
+        ));
+    }
+
+    Ok(())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::Status {} => to_binary(&query_status(deps)?),
@@ -472,7 +2256,194 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::FinishedRound { round_id } => {
             to_binary(&query_finished_round(deps, round_id)?)
         }
+        QueryMsg::AccumulatedFees {} => {
+            to_binary(&query_accumulated_fees(deps)?)
+        }
+        QueryMsg::Referrer { address } => {
+            to_binary(&query_referrer(deps, address)?)
+        }
+        QueryMsg::Odds { round_id } => to_binary(&query_odds(deps, round_id)?),
+        QueryMsg::SimulateBet {
+            round_id,
+            direction,
+            amount,
+            address,
+        } => to_binary(&query_simulate_bet(
+            deps, env, round_id, direction, amount, address,
+        )?),
+        QueryMsg::Solvency {} => to_binary(&query_solvency(deps, env)?),
+        QueryMsg::RoundPayouts { round_id } => {
+            to_binary(&query_round_payouts(deps, round_id)?)
+        }
+        QueryMsg::RoundSummary { round_id } => {
+            to_binary(&query_round_summary(deps, round_id)?)
+        }
+    }
+}
+
+fn query_round_summary(
+    deps: Deps,
+    round_id: Uint128,
+) -> StdResult<RoundSummaryResponse> {
+    match ROUNDS.may_load(deps.storage, round_id.u128())? {
+        Some(round) => Ok(round.into()),
+        None => ARCHIVED_ROUNDS.load(deps.storage, round_id.u128()),
+    }
+}
+
+fn query_round_payouts(
+    deps: Deps,
+    round_id: Uint128,
+) -> StdResult<RoundPayoutsResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let round = ROUNDS.load(deps.storage, round_id.u128())?;
+    let payouts = ROUND_PAYOUTS
+        .may_load(deps.storage, round_id.u128())?
+        .unwrap_or_default();
+
+    Ok(RoundPayoutsResponse {
+        round_id,
+        winning_shares: payout::winning_shares(&round),
+        claimed_shares: payouts.claimed_shares,
+        paid_out: payouts.paid_out,
+        dust: payout::dust(&round, &payouts),
+        dust_swept: payouts.dust_swept,
+        expired: is_expired(deps.storage, &config, round_id.u128()),
+        unclaimed_swept: payouts.unclaimed_swept,
+    })
+}
+
+fn query_solvency(deps: Deps, env: Env) -> StdResult<SolvencyResponse> {
+    let settle_denom = SETTLE_DENOM.load(deps.storage)?;
+    let balance = deps
+        .querier
+        .query_balance(env.contract.address, settle_denom)?
+        .amount;
+    let liabilities = LIABILITIES.may_load(deps.storage)?.unwrap_or_default();
+    let accumulated_fee = Uint128::from(ACCUMULATED_FEE.load(deps.storage)?);
+
+    let total_liabilities = liabilities.bets
+        + liabilities.escrow
+        + liabilities.referral_fees
+        + accumulated_fee;
+
+    Ok(SolvencyResponse {
+        balance,
+        bet_liability: liabilities.bets,
+        escrow_liability: liabilities.escrow,
+        referral_liability: liabilities.referral_fees,
+        accumulated_fee,
+        total_liabilities,
+        surplus: balance.saturating_sub(total_liabilities),
+        deficit: total_liabilities.saturating_sub(balance),
+    })
+}
+
+fn query_odds(deps: Deps, round_id: Uint128) -> StdResult<OddsResponse> {
+    let (bull_amount, bear_amount) = match (
+        NEXT_ROUND.may_load(deps.storage)?,
+        LIVE_ROUND.may_load(deps.storage)?,
+    ) {
+        (Some(round), _) if round.id == round_id => {
+            (round.bull_amount, round.bear_amount)
+        }
+        (_, Some(round)) if round.id == round_id => {
+            (round.bull_amount, round.bear_amount)
+        }
+        _ => {
+            return Err(StdError::generic_err(format!(
+                "Round {} is neither bidding nor live",
+                round_id
+            )))
+        }
+    };
+
+    Ok(OddsResponse {
+        round_id,
+        bull_amount,
+        bear_amount,
+        bull_multiplier: payout::multiplier(
+            bull_amount,
+            bear_amount,
+            &Direction::Bull,
+        ),
+        bear_multiplier: payout::multiplier(
+            bull_amount,
+            bear_amount,
+            &Direction::Bear,
+        ),
+    })
+}
+
+fn query_simulate_bet(
+    deps: Deps,
+    env: Env,
+    round_id: Uint128,
+    direction: Direction,
+    amount: Uint128,
+    address: Option<String>,
+) -> StdResult<SimulateBetResponse> {
+    let mut round = assert_is_current_round(deps, round_id)?;
+    let config = CONFIG.load(deps.storage)?;
+    let bettor = address
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+
+    /* Same fees as execute_bet would charge right now */
+    let BetFees {
+        burn_fee,
+        staker_fee,
+        late_surcharge,
+        net_amount,
+        ..
+    } = compute_bet_fees(
+        deps,
+        &config,
+        bettor.as_ref(),
+        amount,
+        is_late_bet(&config, &round, env.block.time),
+    )?;
+
+    match direction {
+        Direction::Bull => round.bull_amount += net_amount,
+        Direction::Bear => round.bear_amount += net_amount,
     }
+
+    Ok(SimulateBetResponse {
+        burn_fee,
+        staker_fee,
+        late_surcharge,
+        net_amount,
+        multiplier: payout::multiplier(
+            round.bull_amount,
+            round.bear_amount,
+            &direction,
+        ),
+        payout: payout::payout(
+            round.bull_amount,
+            round.bear_amount,
+            &direction,
+            net_amount,
+        ),
+    })
+}
+
+fn query_referrer(deps: Deps, address: String) -> StdResult<ReferrerResponse> {
+    let referral = REFERRALS
+        .may_load(deps.storage, deps.api.addr_validate(&address)?)?
+        .unwrap_or_default();
+
+    Ok(referral)
+}
+
+fn query_accumulated_fees(deps: Deps) -> StdResult<AccumulatedFeesResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let amount = ACCUMULATED_FEE.load(deps.storage)?;
+
+    Ok(AccumulatedFeesResponse {
+        amount: Uint128::from(amount),
+        threshold: config.fund_stakers_threshold,
+    })
 }
 
 fn query_finished_round(
@@ -490,27 +2461,15 @@ fn query_my_current_position(
     let round_id = NEXT_ROUND_ID.load(deps.storage)?;
     let next_bet_key = (round_id - 1, deps.api.addr_validate(&address)?);
 
-    let next_bull_amount: Uint128 = BULL_BETS
-        .load(deps.storage, next_bet_key.clone())
-        .unwrap_or(0u128)
-        .into();
-    let next_bear_amount: Uint128 = BEAR_BETS
-        .load(deps.storage, next_bet_key)
-        .unwrap_or(0u128)
-        .into();
+    let (next_bull_amount, next_bear_amount) =
+        bet_amounts(BETS.may_load(deps.storage, next_bet_key)?);
 
     let mut live_bull_amount: Uint128 = Uint128::zero();
     let mut live_bear_amount: Uint128 = Uint128::zero();
     if round_id > 1 {
         let live_bet_key = (round_id - 2, deps.api.addr_validate(&address)?);
-        live_bull_amount = BULL_BETS
-            .load(deps.storage, live_bet_key.clone())
-            .unwrap_or(0u128)
-            .into();
-        live_bear_amount = BEAR_BETS
-            .load(deps.storage, live_bet_key)
-            .unwrap_or(0u128)
-            .into();
+        (live_bull_amount, live_bear_amount) =
+            bet_amounts(BETS.may_load(deps.storage, live_bet_key)?);
     }
 
     Ok(MyCurrentPositionResponse {
@@ -521,6 +2480,23 @@ fn query_my_current_position(
     })
 }
 
+/* Bull and bear amounts of a wallet's bet */
+fn bet_amounts(maybe_bet: Option<Bet>) -> (Uint128, Uint128) {
+    match maybe_bet {
+        Some(Bet {
+            direction: Direction::Bull,
+            amount,
+            ..
+        }) => (amount, Uint128::zero()),
+        Some(Bet {
+            direction: Direction::Bear,
+            amount,
+            ..
+        }) => (Uint128::zero(), amount),
+        None => (Uint128::zero(), Uint128::zero()),
+    }
+}
+
 fn query_status(deps: Deps) -> StdResult<StatusResponse> {
     let live_round = LIVE_ROUND.may_load(deps.storage)?;
     let bidding_round = NEXT_ROUND.may_load(deps.storage)?;
@@ -564,62 +2540,284 @@ fn assert_only_settle_denom(deps: Deps, coins: &[Coin]) -> StdResult<Uint128> {
     Ok(coins[0].amount)
 }
 
-fn compute_burn_fee(deps: Deps, gross: Uint128) -> StdResult<Uint128> {
-    let burn_fee = CONFIG.load(deps.storage)?.burn_fee;
+/*
+ * Whitelisted accounts get the best tier, everyone else the highest tier
+ * their stake qualifies for
+ */
+fn find_fee_tier(
+    deps: Deps,
+    config: &Config,
+    bettor: &Addr,
+) -> StdResult<Option<FeeTier>> {
+    /* Bettors get the cheapest tier they qualify for */
+    let rate = |tier: &&FeeTier| tier.burn_fee + tier.staker_fee;
+
+    if config.fee_tier_whitelist.contains(bettor) {
+        return Ok(config.fee_tiers.iter().min_by_key(rate).cloned());
+    }
+
+    let staking_addr = match &config.fee_tier_staking_addr {
+        Some(staking_addr) if !config.fee_tiers.is_empty() => staking_addr,
+        _ => return Ok(None),
+    };
+    /* A staking contract that can't answer must not block bets */
+    let staked: StakedBalanceAtHeightResponse =
+        match deps.querier.query_wasm_smart(
+            staking_addr,
+            &StakeCw20QueryMsg::StakedBalanceAtHeight {
+                address: bettor.to_string(),
+                height: None,
+            },
+        ) {
+            Ok(staked) => staked,
+            Err(_) => return Ok(None),
+        };
+
+    Ok(config
+        .fee_tiers
+        .iter()
+        .filter(|tier| staked.balance >= tier.min_staked)
+        .min_by_key(rate)
+        .cloned())
+}
+
+fn compute_burn_fee(
+    deps: Deps,
+    gross: Uint128,
+    fee_tier: &Option<FeeTier>,
+) -> StdResult<Uint128> {
+    let burn_fee = match fee_tier {
+        Some(tier) => tier.burn_fee,
+        None => CONFIG.load(deps.storage)?.burn_fee,
+    };
 
     burn_fee
         .checked_multiply_ratio(gross, FEE_PRECISION * 100)
         .map_err(|e| StdError::generic_err(e.to_string()))
 }
 
-fn compute_staker_fee(deps: Deps, gross: Uint128) -> StdResult<Uint128> {
-    let staker_fee = CONFIG.load(deps.storage)?.staker_fee;
+fn compute_staker_fee(
+    deps: Deps,
+    gross: Uint128,
+    fee_tier: &Option<FeeTier>,
+) -> StdResult<Uint128> {
+    let staker_fee = match fee_tier {
+        Some(tier) => tier.staker_fee,
+        None => CONFIG.load(deps.storage)?.staker_fee,
+    };
 
     staker_fee
         .checked_multiply_ratio(gross, FEE_PRECISION * 100)
         .map_err(|e| StdError::generic_err(e.to_string()))
 }
 
+fn compute_referral_fee(deps: Deps, staker_fee: Uint128) -> StdResult<Uint128> {
+    let referral_share = CONFIG.load(deps.storage)?.referral_share;
+
+    referral_share
+        .checked_multiply_ratio(staker_fee, FEE_PRECISION * 100)
+        .map_err(|e| StdError::generic_err(e.to_string()))
+}
+
+/* None when settling at round times and no price is recent enough */
 fn compute_round_open(
     deps: Deps,
     env: Env,
     round: &NextRound,
-) -> StdResult<LiveRound> {
-    /* TODO */
-    let open_price = get_current_price(deps)?;
+) -> StdResult<Option<LiveRound>> {
     let config = CONFIG.load(deps.storage)?;
 
-    Ok(LiveRound {
+    let open_time = if config.settle_at_round_times {
+        round.open_time
+    } else {
+        env.block.time
+    };
+    let (open_price, price_expo, open_price_time) =
+        match settlement_price(deps, &config, open_time)? {
+            Some(price) => price,
+            None => return Ok(None),
+        };
+
+    Ok(Some(LiveRound {
         id: round.id,
         bid_time: round.bid_time,
-        open_time: env.block.time,
-        close_time: env
-            .block
-            .time
+        open_time,
+        close_time: open_time
             .plus_seconds(config.next_round_seconds.u128() as u64),
         open_price,
         bull_amount: round.bull_amount,
         bear_amount: round.bear_amount,
-    })
+        open_price_time,
+        price_expo,
+    }))
+}
+
+fn void_round(round: &NextRound) -> FinishedRound {
+    FinishedRound {
+        id: round.id,
+        bid_time: round.bid_time,
+        open_time: round.open_time,
+        close_time: round.close_time,
+        open_price: Uint128::zero(),
+        close_price: Uint128::zero(),
+        winner: None,
+        bull_amount: round.bull_amount,
+        bear_amount: round.bear_amount,
+        open_price_time: None,
+        close_price_time: None,
+        voided: true,
+        price_expo: 0,
+    }
+}
+
+fn oracle_ticker(config: &Config) -> String {
+    config
+        .ticker
+        .clone()
+        .unwrap_or_else(|| DEFAULT_TICKER.to_string())
 }
 
-fn get_current_price(deps: Deps) -> StdResult<Uint128> {
+fn get_current_price(deps: Deps) -> StdResult<PriceResponse> {
     let config = CONFIG.load(deps.storage)?;
 
-    let price: Uint128 =
+    let price: PriceResponse =
         deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
             contract_addr: config.fast_oracle_addr.to_string(),
-            msg: to_binary(&FastOracleQueryMsg::Price {})?,
+            msg: to_binary(&FastOracleQueryMsg::Price {
+                ticker: oracle_ticker(&config),
+            })?,
         }))?;
 
     Ok(price)
 }
 
+/*
+ * Price, exponent and oracle publish time a round opens or closes on at
+ * `time`. None when settling at round times and the oracle has no price then
+ */
+fn settlement_price(
+    deps: Deps,
+    config: &Config,
+    time: Timestamp,
+) -> StdResult<Option<(Uint128, i32, Option<Timestamp>)>> {
+    let window = config.twap_window_seconds.u128() as u64;
+
+    if config.settle_at_round_times {
+        let entry = match get_price_at(deps, config, time)? {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        if window == 0 {
+            return Ok(Some((entry.price, entry.expo, Some(entry.updated_at))));
+        }
+
+        /* A window reaching before the first price voids the round too */
+        let twap = get_twap(deps, config, time, window)?;
+        return Ok(twap
+            .twap
+            .map(|value| (value, twap.expo, Some(entry.updated_at))));
+    }
+
+    /* Refuse to settle on a feed without a fresh quorum, TWAP or not */
+    let price = get_current_price(deps)?;
+    if window == 0 {
+        return Ok(Some((price.value, price.expo, None)));
+    }
+
+    let twap = get_twap(deps, config, time, window)?;
+    let value = twap.twap.ok_or_else(|| {
+        StdError::generic_err(format!(
+            "The oracle has no TWAP over the {} seconds before {}",
+            window, time
+        ))
+    })?;
+    Ok(Some((value, twap.expo, None)))
+}
+
+/* Oracle TWAP over the `window` seconds up to `end` */
+fn get_twap(
+    deps: Deps,
+    config: &Config,
+    end: Timestamp,
+    window: u64,
+) -> StdResult<TwapResponse> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: config.fast_oracle_addr.to_string(),
+        msg: to_binary(&FastOracleQueryMsg::Twap {
+            ticker: oracle_ticker(config),
+            start: end.minus_seconds(window),
+            end,
+        })?,
+    }))
+}
+
+/* Last oracle price at or before `time`, None if older than the tolerance */
+fn get_price_at(
+    deps: Deps,
+    config: &Config,
+    time: Timestamp,
+) -> StdResult<Option<PriceEntry>> {
+    let res: PriceAtResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: config.fast_oracle_addr.to_string(),
+            msg: to_binary(&FastOracleQueryMsg::PriceAt {
+                ticker: oracle_ticker(config),
+                time,
+            })?,
+        }))?;
+
+    Ok(res.entry.filter(|entry| {
+        time.seconds().saturating_sub(entry.updated_at.seconds())
+            <= config.price_tolerance_seconds.u128() as u64
+    }))
+}
+
+/* `value` * 10^from_expo expressed with `to_expo`, so prices stay comparable */
+fn rescale(value: Uint128, from_expo: i32, to_expo: i32) -> StdResult<Uint128> {
+    let factor =
+        Uint128::new(10).checked_pow((from_expo - to_expo).unsigned_abs())?;
+    if from_expo >= to_expo {
+        Ok(value.checked_mul(factor)?)
+    } else {
+        Ok(value / factor)
+    }
+}
+
 fn compute_round_close(
     deps: Deps,
+    env: &Env,
     round: &LiveRound,
 ) -> StdResult<FinishedRound> {
-    let close_price = get_current_price(deps)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    let close_time = if config.settle_at_round_times {
+        round.close_time
+    } else {
+        env.block.time
+    };
+    let (close_value, close_expo, close_price_time) =
+        match settlement_price(deps, &config, close_time)? {
+            Some(price) => price,
+            None => {
+                return Ok(FinishedRound {
+                    id: round.id,
+                    bid_time: round.bid_time,
+                    open_time: round.open_time,
+                    close_time: round.close_time,
+                    open_price: round.open_price,
+                    close_price: Uint128::zero(),
+                    winner: None,
+                    bull_amount: round.bull_amount,
+                    bear_amount: round.bear_amount,
+                    open_price_time: round.open_price_time,
+                    close_price_time: None,
+                    voided: true,
+                    price_expo: round.price_expo,
+                })
+            }
+        };
+    /* The feed's exponent may have changed while the round was live */
+    let close_price = rescale(close_value, close_expo, round.price_expo)?;
 
     let winner = match close_price.cmp(&round.open_price) {
         std::cmp::Ordering::Greater =>
@@ -649,9 +2847,22 @@ fn compute_round_close(
         bull_amount: round.bull_amount,
         winner,
         close_price,
+        open_price_time: round.open_price_time,
+        close_price_time,
+        voided: false,
+        price_expo: round.price_expo,
     })
 }
 
+fn assert_migrated(storage: &dyn Storage) -> StdResult<()> {
+    if LEGACY_MIGRATION.may_load(storage)?.is_some() {
+        return Err(StdError::generic_err(
+            "Legacy state is being migrated, see MigrateLegacy",
+        ));
+    }
+    Ok(())
+}
+
 fn assert_not_haulted(deps: Deps) -> StdResult<bool> {
     let is_haulted = IS_HAULTED.load(deps.storage)?;
     if is_haulted {
@@ -1,5 +1,6 @@
 pub mod contract;
 pub mod error;
+pub mod payout;
 pub mod state;
 
 pub use forecast_deliverdao::price_prediction::*;
@@ -0,0 +1,106 @@
+use crate::{Direction, FinishedRound, RoundPayouts};
+use cosmwasm_std::{Decimal, Uint128};
+
+/*
+ * Pari-mutuel payout math shared by claims and the odds queries
+ */
+
+/* Both sides need to be taken for a round to pay out, else it refunds */
+pub fn has_counter_party(bull_amount: Uint128, bear_amount: Uint128) -> bool {
+    !bull_amount.is_zero() && !bear_amount.is_zero()
+}
+
+/* What `shares` on the `winner` side receive from the whole pool */
+pub fn payout(
+    bull_amount: Uint128,
+    bear_amount: Uint128,
+    winner: &Direction,
+    shares: Uint128,
+) -> Uint128 {
+    if !has_counter_party(bull_amount, bear_amount) {
+        return shares;
+    }
+
+    let pool_shares = bull_amount + bear_amount;
+    match winner {
+        Direction::Bull => pool_shares.multiply_ratio(shares, bull_amount),
+        Direction::Bear => pool_shares.multiply_ratio(shares, bear_amount),
+    }
+}
+
+/* Payout per unit bet on `dir` if it wins; 1 while the round would refund */
+pub fn multiplier(
+    bull_amount: Uint128,
+    bear_amount: Uint128,
+    dir: &Direction,
+) -> Decimal {
+    if !has_counter_party(bull_amount, bear_amount) {
+        return Decimal::one();
+    }
+
+    let pool_shares = bull_amount + bear_amount;
+    match dir {
+        Direction::Bull => Decimal::from_ratio(pool_shares, bull_amount),
+        Direction::Bear => Decimal::from_ratio(pool_shares, bear_amount),
+    }
+}
+
+/* What a wallet holding these shares can claim from a finished round */
+pub fn round_winnings(
+    round: &FinishedRound,
+    bull_shares: Uint128,
+    bear_shares: Uint128,
+) -> Uint128 {
+    if !has_counter_party(round.bull_amount, round.bear_amount) {
+        /* Nobody to bet against, return funds */
+        return bull_shares + bear_shares;
+    }
+
+    match &round.winner {
+        Some(Direction::Bull) => payout(
+            round.bull_amount,
+            round.bear_amount,
+            &Direction::Bull,
+            bull_shares,
+        ),
+        Some(Direction::Bear) => payout(
+            round.bull_amount,
+            round.bear_amount,
+            &Direction::Bear,
+            bear_shares,
+        ),
+        /* Give back what the wallet bet in case of tie */
+        None => bull_shares + bear_shares,
+    }
+}
+
+/* Shares whose payout is rounded down; ties and refunds pay out exactly */
+pub fn winning_shares(round: &FinishedRound) -> Uint128 {
+    if !has_counter_party(round.bull_amount, round.bear_amount) {
+        return Uint128::zero();
+    }
+
+    match &round.winner {
+        Some(Direction::Bull) => round.bull_amount,
+        Some(Direction::Bear) => round.bear_amount,
+        None => Uint128::zero(),
+    }
+}
+
+/* Pool left after every winner claimed, None while claims are pending */
+pub fn dust(round: &FinishedRound, payouts: &RoundPayouts) -> Option<Uint128> {
+    let winning_shares = winning_shares(round);
+    if winning_shares.is_zero() || payouts.claimed_shares < winning_shares {
+        return None;
+    }
+
+    Some(unclaimed(round, payouts))
+}
+
+/* Pool not yet paid out or swept, including unclaimed refunds */
+pub fn unclaimed(round: &FinishedRound, payouts: &RoundPayouts) -> Uint128 {
+    (round.bull_amount + round.bear_amount)
+        .saturating_sub(payouts.paid_out)
+        .saturating_sub(payouts.dust_swept)
+        .saturating_sub(payouts.unclaimed_swept)
+}
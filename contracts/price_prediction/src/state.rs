@@ -1,5 +1,8 @@
-use crate::{Config, FinishedRound, LiveRound, NextRound};
-use cosmwasm_std::Addr;
+use crate::{
+    Bet, Commitment, ConditionalBet, Config, FinishedRound, LegacyMigration,
+    Liabilities, LiveRound, NextRound, Referral, RoundPayouts, RoundSummary,
+};
+use cosmwasm_std::{Addr, Uint128};
 use cw_storage_plus::{Item, Map};
 
 pub const IS_HAULTED: Item<bool> = Item::new("is_haulted");
@@ -11,14 +14,44 @@ pub const NEXT_ROUND: Item<NextRound> = Item::new("next_round");
 pub const LIVE_ROUND: Item<LiveRound> = Item::new("live_round");
 /* Winnings (per-wallet) that can be claimed from the pool  */
 pub const SETTLE_DENOM: Item<String> = Item::new("settle_denom");
-/* Bears in a given round */
-pub const BEAR_BETS: Map<(u128, Addr), u128> = Map::new("bear_bets");
-/* Bulls in a given round */
-pub const BULL_BETS: Map<(u128, Addr), u128> = Map::new("bull_bets");
-/* Bulls in a given round */
+/* Bets in a given round */
+pub const BETS: Map<(u128, Addr), Bet> = Map::new("bets");
+/* Staker fee collected and not yet sent to the stakers */
 pub const ACCUMULATED_FEE: Item<u128> = Item::new("accumulated_fee");
-
-pub const MY_CLAIMED_ROUNDS: Map<(Addr, u128), bool> =
-    Map::new("my_claimed_rounds");
+/* Staker fee sent by the last automatic funding, restored if it failed */
+pub const FUNDING: Item<u128> = Item::new("funding");
 
 pub const ROUNDS: Map<u128, FinishedRound> = Map::new("rounds");
+
+/* Settled rounds removed from ROUNDS by Prune */
+pub const ARCHIVED_ROUNDS: Map<u128, RoundSummary> =
+    Map::new("archived_rounds");
+
+/* Fees earned by frontends and accounts referring bets */
+pub const REFERRALS: Map<Addr, Referral> = Map::new("referrals");
+
+/* Bets escrowed until the round opens, counted only if the odds hold */
+pub const CONDITIONAL_BETS: Map<(u128, Addr), ConditionalBet> =
+    Map::new("conditional_bets");
+
+/* Bets waiting to be revealed, in commit-reveal mode */
+pub const COMMITMENTS: Map<(u128, Addr), Commitment> = Map::new("commitments");
+
+/* What the contract owes; compared against its balance by Solvency */
+pub const LIABILITIES: Item<Liabilities> = Item::new("liabilities");
+
+/* Winnings collected per finished round, to find the rounding dust */
+pub const ROUND_PAYOUTS: Map<u128, RoundPayouts> = Map::new("round_payouts");
+
+/* Set while an upgrade from before 0.2.0 is being migrated */
+pub const LEGACY_MIGRATION: Item<LegacyMigration> =
+    Item::new("legacy_migration");
+/* Per finished round: still owed and winning shares not yet claimed */
+pub const LEGACY_OUTSTANDING: Map<u128, (Uint128, Uint128)> =
+    Map::new("legacy_outstanding");
+
+/* Pre-BETS storage, only read by migrate */
+pub const LEGACY_BEAR_BETS: Map<(u128, Addr), u128> = Map::new("bear_bets");
+pub const LEGACY_BULL_BETS: Map<(u128, Addr), u128> = Map::new("bull_bets");
+pub const LEGACY_MY_CLAIMED_ROUNDS: Map<(Addr, u128), bool> =
+    Map::new("my_claimed_rounds");
@@ -1,24 +1,105 @@
+use crate::state::{
+    ARCHIVED_ROUNDS, BETS, COMMITMENTS, CONFIG, LEGACY_BEAR_BETS,
+    LEGACY_BULL_BETS, LEGACY_MIGRATION, LEGACY_MY_CLAIMED_ROUNDS, LIABILITIES,
+    LIVE_ROUND, ROUNDS,
+};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
 use cosmwasm_std::{
-    coins, to_binary, Addr, Binary, BlockInfo, Coin, CosmosMsg, Empty,
-    Response, StdResult, Timestamp, Uint128, WasmMsg,
+    coins, from_slice, to_binary, to_vec, Addr, Binary, BlockInfo, Coin,
+    CosmosMsg, Decimal, Empty, Response, StdResult, Timestamp, Uint128,
+    WasmMsg,
 };
 use cw_multi_test::{App, Contract, ContractWrapper, Executor};
 use forecast_deliverdao::fast_oracle::{
     msg::ExecuteMsg as FastOracleExecuteMsg,
     msg::InstantiateMsg as FastOracleInstantiateMsg,
     msg::QueryMsg as FastOracleQueryMsg,
+    response::{PriceAtResponse, PriceResponse, TwapResponse},
+    PriceEntry, DEFAULT_TICKER,
 };
 use forecast_deliverdao::price_prediction::{
-    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
-    response::{ConfigResponse, StatusResponse},
-    Config, PartialConfig,
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg, SudoMsg},
+    response::{
+        AccumulatedFeesResponse, ConfigResponse, OddsResponse,
+        ReferrerResponse, RoundPayoutsResponse, RoundResponse,
+        RoundSummaryResponse, SimulateBetResponse, SolvencyResponse,
+        StatusResponse,
+    },
+    Bet, Commitment, Config, Direction, FeeTier, FinishedRound, Liabilities,
+    LiveRound, MigrateMsg, PartialConfig, RewardSink,
+};
+use stake_cw20::msg::{
+    QueryMsg as StakeCw20QueryMsg, ReceiveMsg as StakeCw20ReceiveMsg,
+    StakedBalanceAtHeightResponse,
 };
-use stake_cw20::msg::ReceiveMsg as StakeCw20ReceiveMsg;
 use std::borrow::BorrowMut;
-use std::convert::TryInto;
 
 const SETTLE_DENOM: &str = "earth";
 
+fn base_config() -> Config {
+    Config {
+        next_round_seconds: Uint128::new(600u128),
+        fast_oracle_addr: Addr::unchecked("fast_oracle"),
+        cw20_stake_external_rewards_addr: Addr::unchecked("external_rewards"),
+        minimum_bet: Uint128::new(1u128),
+        burn_addr: Addr::unchecked("burn"),
+        burn_fee: Uint128::new(100u128),
+        staker_fee: Uint128::new(200u128),
+        fund_stakers_threshold: Uint128::zero(),
+        auto_fund_stakers: false,
+        reward_sink: None,
+        referral_share: Uint128::zero(),
+        fee_tiers: vec![],
+        fee_tier_whitelist: vec![],
+        fee_tier_staking_addr: None,
+        claim_expiry_rounds: Uint128::zero(),
+        commit_reveal: false,
+        reveal_window_seconds: Uint128::zero(),
+        forfeit_unrevealed: true,
+        snipe_window_seconds: Uint128::zero(),
+        snipe_ratio_threshold: Uint128::zero(),
+        snipe_extension_seconds: Uint128::zero(),
+        max_snipe_extension_seconds: Uint128::zero(),
+        late_bet_surcharge: Uint128::zero(),
+        ticker: None,
+        settle_at_round_times: false,
+        price_tolerance_seconds: Uint128::zero(),
+        twap_window_seconds: Uint128::zero(),
+    }
+}
+
+fn empty_partial_config() -> PartialConfig {
+    PartialConfig {
+        next_round_seconds: None,
+        fast_oracle_addr: None,
+        cw20_stake_external_rewards_addr: None,
+        minimum_bet: None,
+        burn_addr: None,
+        burn_fee: None,
+        staker_fee: None,
+        fund_stakers_threshold: None,
+        auto_fund_stakers: None,
+        reward_sink: None,
+        referral_share: None,
+        fee_tiers: None,
+        fee_tier_whitelist: None,
+        fee_tier_staking_addr: None,
+        claim_expiry_rounds: None,
+        commit_reveal: None,
+        reveal_window_seconds: None,
+        forfeit_unrevealed: None,
+        snipe_window_seconds: None,
+        snipe_ratio_threshold: None,
+        snipe_extension_seconds: None,
+        max_snipe_extension_seconds: None,
+        late_bet_surcharge: None,
+        ticker: None,
+        settle_at_round_times: None,
+        price_tolerance_seconds: None,
+        twap_window_seconds: None,
+    }
+}
+
 fn mock_app() -> App {
     App::default()
 }
@@ -28,7 +109,9 @@ pub fn contract_price_prediction() -> Box<dyn Contract<Empty>> {
         crate::contract::execute,
         crate::contract::instantiate,
         crate::contract::query,
-    );
+    )
+    .with_sudo(crate::contract::sudo)
+    .with_reply(crate::contract::reply);
     Box::new(contract)
 }
 
@@ -43,8 +126,22 @@ pub fn contract_external_rewards() -> Box<dyn Contract<Empty>> {
         |_deps, _, _, _: FastOracleInstantiateMsg| -> StdResult<Response> {
             Ok(Response::default())
         },
-        |_deps, _, _msg: FastOracleQueryMsg| -> StdResult<Binary> {
-            to_binary(&{})
+        |_deps, _, msg: StakeCw20QueryMsg| -> StdResult<Binary> {
+            match msg {
+                StakeCw20QueryMsg::StakedBalanceAtHeight {
+                    address, ..
+                } => {
+                    let balance = match address.as_str() {
+                        "staker" => Uint128::new(500u128),
+                        _ => Uint128::zero(),
+                    };
+                    to_binary(&StakedBalanceAtHeightResponse {
+                        balance,
+                        height: 0,
+                    })
+                }
+                _ => to_binary(&{}),
+            }
         },
     );
     Box::new(contract)
@@ -52,30 +149,119 @@ pub fn contract_external_rewards() -> Box<dyn Contract<Empty>> {
 
 pub fn contract_fast_oracle() -> Box<dyn Contract<Empty>> {
     let contract = ContractWrapper::new(
-        |deps, _, _info, msg: FastOracleExecuteMsg| -> StdResult<Response> {
+        |deps, env, info, msg: FastOracleExecuteMsg| -> StdResult<Response> {
             match msg {
-                FastOracleExecuteMsg::Update { price } => {
-                    deps.storage.set(b"price", &price.to_be_bytes());
+                FastOracleExecuteMsg::Update { ticker, price } => {
+                    let entry = PriceEntry {
+                        price,
+                        updated_at: env.block.time,
+                        height: env.block.height,
+                        triggered_by: info.sender,
+                        expo: 0,
+                    };
+                    if let Some(previous) =
+                        deps.storage.get(format!("price:{}", ticker).as_bytes())
+                    {
+                        deps.storage.set(
+                            format!("previous_price:{}", ticker).as_bytes(),
+                            &previous,
+                        );
+                    }
+                    deps.storage.set(
+                        format!("price:{}", ticker).as_bytes(),
+                        &to_vec(&entry)?,
+                    );
                     Ok(Response::default())
                 }
-                FastOracleExecuteMsg::Owner { owner: _ } => todo!(),
+                _ => todo!(),
             }
         },
-        |deps, _, _, _: FastOracleInstantiateMsg| -> StdResult<Response> {
-            deps.storage
-                .set(b"price", &Uint128::new(1_000_000u128).to_be_bytes());
+        |deps, env, info, _: FastOracleInstantiateMsg| -> StdResult<Response> {
+            let entry = PriceEntry {
+                price: Uint128::new(1_000_000u128),
+                updated_at: env.block.time,
+                height: env.block.height,
+                triggered_by: info.sender,
+                expo: 0,
+            };
+            deps.storage.set(
+                format!("price:{}", DEFAULT_TICKER).as_bytes(),
+                &to_vec(&entry)?,
+            );
             Ok(Response::default())
         },
         |deps, _, msg: FastOracleQueryMsg| -> StdResult<Binary> {
             match msg {
-                FastOracleQueryMsg::Price {} => {
-                    let res = deps.storage.get(b"price").unwrap_or_default();
-                    let price = Uint128::from(u128::from_be_bytes(
-                        res.as_slice().try_into().unwrap(),
-                    ));
-
-                    to_binary(&price)
+                FastOracleQueryMsg::Price { ticker } => {
+                    let res = deps
+                        .storage
+                        .get(format!("price:{}", ticker).as_bytes())
+                        .unwrap_or_default();
+                    let entry: PriceEntry = from_slice(&res)?;
+
+                    to_binary(&PriceResponse {
+                        value: entry.price,
+                        expo: entry.expo,
+                        timestamp: entry.updated_at,
+                    })
+                }
+                /* Only the latest entry is kept */
+                FastOracleQueryMsg::PriceAt { ticker, time } => {
+                    let res = deps
+                        .storage
+                        .get(format!("price:{}", ticker).as_bytes())
+                        .unwrap_or_default();
+                    let entry: PriceEntry = from_slice(&res)?;
+
+                    to_binary(&PriceAtResponse {
+                        entry: Some(entry).filter(|e| e.updated_at <= time),
+                    })
+                }
+                /* Only the last two entries are kept */
+                FastOracleQueryMsg::Twap { ticker, start, end } => {
+                    let res = deps
+                        .storage
+                        .get(format!("price:{}", ticker).as_bytes())
+                        .unwrap_or_default();
+                    let latest: PriceEntry = from_slice(&res)?;
+                    let previous: PriceEntry = match deps
+                        .storage
+                        .get(format!("previous_price:{}", ticker).as_bytes())
+                    {
+                        Some(res) => from_slice(&res)?,
+                        None => latest.clone(),
+                    };
+                    if previous.updated_at > start {
+                        return to_binary(&TwapResponse {
+                            ticker,
+                            twap: None,
+                            expo: latest.expo,
+                            start,
+                            end,
+                        });
+                    }
+
+                    let split = latest
+                        .updated_at
+                        .seconds()
+                        .clamp(start.seconds(), end.seconds());
+                    let sum = previous.price
+                        * Uint128::from(split - start.seconds())
+                        + latest.price * Uint128::from(end.seconds() - split);
+
+                    to_binary(&TwapResponse {
+                        ticker,
+                        twap: Some(
+                            sum / Uint128::from(
+                                end.seconds() - start.seconds(),
+                            ),
+                        ),
+                        expo: latest.expo,
+                        start,
+                        end,
+                    })
                 }
+                _ => todo!(),
             }
         },
     );
@@ -90,7 +276,11 @@ fn update_price(
 ) {
     let update_price_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: config.fast_oracle_addr.to_string(),
-        msg: to_binary(&FastOracleExecuteMsg::Update { price }).unwrap(),
+        msg: to_binary(&FastOracleExecuteMsg::Update {
+            ticker: DEFAULT_TICKER.to_string(),
+            price,
+        })
+        .unwrap(),
         funds: vec![],
     });
 
@@ -166,6 +356,10 @@ fn create_prediction_market(
         .unwrap();
 
     msg.config.fast_oracle_addr = fast_oracle_addr;
+    /* The mock rewards contract answers staking queries too */
+    if msg.config.fee_tier_staking_addr.is_some() {
+        msg.config.fee_tier_staking_addr = Some(external_rewards_addr.clone());
+    }
     msg.config.cw20_stake_external_rewards_addr = external_rewards_addr;
 
     router
@@ -191,15 +385,7 @@ fn proper_initialization() {
         router.bank.init_balance(storage, &owner, funds).unwrap()
     });
 
-    let default_config: Config = Config {
-        next_round_seconds: Uint128::new(600u128),
-        fast_oracle_addr: Addr::unchecked("fast_oracle"),
-        cw20_stake_external_rewards_addr: Addr::unchecked("external_rewards"),
-        minimum_bet: Uint128::new(1u128),
-        burn_addr: Addr::unchecked("burn"),
-        burn_fee: Uint128::new(100u128),
-        staker_fee: Uint128::new(200u128),
-    };
+    let default_config: Config = base_config();
 
     let prediction_market_addr =
         create_prediction_market(&mut router, &owner, default_config.clone());
@@ -233,17 +419,7 @@ fn create_market_and_start(
                 create_prediction_market(router, &owner, config.clone());
         }
         None => {
-            let default_config: Config = Config {
-                next_round_seconds: Uint128::new(600u128),
-                fast_oracle_addr: Addr::unchecked("fast_oracle"),
-                cw20_stake_external_rewards_addr: Addr::unchecked(
-                    "external_rewards",
-                ),
-                minimum_bet: Uint128::new(1u128),
-                staker_fee: Uint128::new(200u128),
-                burn_addr: Addr::unchecked("burn"),
-                burn_fee: Uint128::new(100u128),
-            };
+            let default_config: Config = base_config();
 
             prediction_market_addr = create_prediction_market(
                 router,
@@ -311,6 +487,9 @@ fn proper_betting() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBear {
             round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -380,6 +559,9 @@ fn proper_close_round_up_win() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBear {
             round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -396,6 +578,9 @@ fn proper_close_round_up_win() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBull {
             round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -549,6 +734,9 @@ fn proper_close_round_down_win() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBull {
             round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -565,6 +753,9 @@ fn proper_close_round_down_win() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBear {
             round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -659,6 +850,9 @@ fn proper_deny_betting_on_closed_round() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBear {
             round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -694,6 +888,9 @@ fn proper_deny_betting_on_live_round() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBear {
             round_id: Uint128::new(1u128),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -710,6 +907,9 @@ fn proper_deny_betting_on_live_round() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBear {
             round_id: Uint128::new(3u128),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -793,6 +993,9 @@ fn proper_deny_betting_twice() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBear {
             round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -805,6 +1008,9 @@ fn proper_deny_betting_twice() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBear {
             round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -831,6 +1037,9 @@ fn proper_deny_betting_twice() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBear {
             round_id: Uint128::new(1),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -843,6 +1052,9 @@ fn proper_deny_betting_twice() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBear {
             round_id: Uint128::new(1),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -912,6 +1124,9 @@ fn proper_haulting_and_resume_games() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBear {
             round_id: Uint128::new(2),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -941,6 +1156,9 @@ fn proper_haulting_and_resume_games() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBear {
             round_id: Uint128::new(2),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -973,13 +1191,9 @@ fn proper_updating_config() {
     let funds = coins(2000, SETTLE_DENOM);
 
     let default_config: Config = Config {
-        next_round_seconds: Uint128::new(600u128),
-        fast_oracle_addr: Addr::unchecked("fast_oracle"),
-        cw20_stake_external_rewards_addr: Addr::unchecked("external_rewards"),
-        minimum_bet: Uint128::new(1u128),
         staker_fee: Uint128::new(300u128),
-        burn_addr: Addr::unchecked("burn"),
         burn_fee: Uint128::new(300u128),
+        ..base_config()
     };
 
     let prediction_market_addr = create_market_and_start(
@@ -1009,8 +1223,7 @@ fn proper_updating_config() {
                     new_cw20_stake_external_rewards_addr.clone(),
                 ),
                 staker_fee: Some(new_staker_fee),
-                burn_addr: None,
-                burn_fee: None,
+                ..empty_partial_config()
             },
         })
         .unwrap(),
@@ -1068,6 +1281,9 @@ fn proper_close_round_tie() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBull {
             round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -1084,6 +1300,9 @@ fn proper_close_round_tie() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBear {
             round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -1182,6 +1401,9 @@ fn proper_return_funds_if_no_counter_party() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::BetBull {
             round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
         })
         .unwrap(),
         funds: vec![Coin {
@@ -1249,13 +1471,10 @@ fn proper_change_round_duration() {
     let funds = coins(2000, SETTLE_DENOM);
 
     let default_config: Config = Config {
-        next_round_seconds: Uint128::new(600u128),
-        fast_oracle_addr: Addr::unchecked("fast_oracle"),
         cw20_stake_external_rewards_addr: Addr::unchecked("treasury"),
-        minimum_bet: Uint128::new(1u128),
         staker_fee: Uint128::new(300u128),
-        burn_addr: Addr::unchecked("burn"),
         burn_fee: Uint128::new(300u128),
+        ..base_config()
     };
 
     let prediction_market_addr = create_market_and_start(
@@ -1273,13 +1492,8 @@ fn proper_change_round_duration() {
         contract_addr: prediction_market_addr.to_string(),
         msg: to_binary(&ExecuteMsg::UpdateConfig {
             config: PartialConfig {
-                minimum_bet: None,
-                fast_oracle_addr: None,
                 next_round_seconds: Some(new_next_round_seconds),
-                cw20_stake_external_rewards_addr: None,
-                staker_fee: None,
-                burn_addr: None,
-                burn_fee: None,
+                ..empty_partial_config()
             },
         })
         .unwrap(),
@@ -1302,3 +1516,2577 @@ fn proper_change_round_duration() {
         next_round_status.bidding_round.unwrap().open_time
     );
 }
+
+#[test]
+fn proper_fund_stakers_threshold() {
+    let mut router = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+
+    let default_config: Config = Config {
+        fund_stakers_threshold: Uint128::new(5u128),
+        ..base_config()
+    };
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        Some(default_config),
+        owner.clone(),
+        funds,
+    );
+
+    let config: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+
+    let fund_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::FundStakers {}).unwrap(),
+        funds: vec![],
+    });
+
+    // Nothing accrued yet, funding is skipped instead of failing
+    router
+        .execute_multi(owner.clone(), [fund_msg.clone()].to_vec())
+        .unwrap();
+
+    let bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBull {
+            round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: vec![Coin {
+            denom: SETTLE_DENOM.to_string(),
+            amount: Uint128::new(100u128),
+        }],
+    });
+
+    router
+        .execute_multi(owner.clone(), [bet_msg].to_vec())
+        .unwrap();
+
+    // 2 accrued, still below the threshold
+    router
+        .execute_multi(owner.clone(), [fund_msg.clone()].to_vec())
+        .unwrap();
+
+    let accumulated: AccumulatedFeesResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr.clone(),
+            &QueryMsg::AccumulatedFees {},
+        )
+        .unwrap();
+
+    assert_eq!(accumulated.amount, Uint128::new(2u128));
+    assert_eq!(accumulated.threshold, Uint128::new(5u128));
+
+    start_next_round(&mut router, &prediction_market_addr, &owner);
+
+    let bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBull {
+            round_id: Uint128::new(1u128),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: vec![Coin {
+            denom: SETTLE_DENOM.to_string(),
+            amount: Uint128::new(200u128),
+        }],
+    });
+
+    router
+        .execute_multi(owner.clone(), [bet_msg].to_vec())
+        .unwrap();
+
+    let external_rewards_amount = router
+        .wrap()
+        .query_balance(
+            config.cw20_stake_external_rewards_addr.clone(),
+            SETTLE_DENOM,
+        )
+        .unwrap()
+        .amount;
+
+    assert_eq!(external_rewards_amount, Uint128::zero());
+
+    // 6 accrued, crosses the threshold
+    router
+        .execute_multi(owner.clone(), [fund_msg].to_vec())
+        .unwrap();
+
+    let external_rewards_amount = router
+        .wrap()
+        .query_balance(
+            config.cw20_stake_external_rewards_addr.clone(),
+            SETTLE_DENOM,
+        )
+        .unwrap()
+        .amount;
+
+    assert_eq!(external_rewards_amount, Uint128::new(6u128));
+
+    let accumulated: AccumulatedFeesResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr, &QueryMsg::AccumulatedFees {})
+        .unwrap();
+
+    assert_eq!(accumulated.amount, Uint128::zero());
+}
+
+#[test]
+fn proper_auto_fund_stakers_on_close_round() {
+    let mut router = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+
+    let default_config: Config = Config {
+        fund_stakers_threshold: Uint128::new(3u128),
+        auto_fund_stakers: true,
+        ..base_config()
+    };
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        Some(default_config),
+        owner.clone(),
+        funds,
+    );
+
+    let config: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+
+    let bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBull {
+            round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: vec![Coin {
+            denom: SETTLE_DENOM.to_string(),
+            amount: Uint128::new(100u128),
+        }],
+    });
+
+    router
+        .execute_multi(owner.clone(), [bet_msg].to_vec())
+        .unwrap();
+
+    // 2 accrued, below the threshold so closing does not fund
+    start_next_round(&mut router, &prediction_market_addr, &owner);
+
+    let external_rewards_amount = router
+        .wrap()
+        .query_balance(
+            config.cw20_stake_external_rewards_addr.clone(),
+            SETTLE_DENOM,
+        )
+        .unwrap()
+        .amount;
+
+    assert_eq!(external_rewards_amount, Uint128::zero());
+
+    let bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBear {
+            round_id: Uint128::new(1u128),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: vec![Coin {
+            denom: SETTLE_DENOM.to_string(),
+            amount: Uint128::new(100u128),
+        }],
+    });
+
+    router
+        .execute_multi(owner.clone(), [bet_msg].to_vec())
+        .unwrap();
+
+    start_next_round(&mut router, &prediction_market_addr, &owner);
+
+    let external_rewards_amount = router
+        .wrap()
+        .query_balance(
+            config.cw20_stake_external_rewards_addr.clone(),
+            SETTLE_DENOM,
+        )
+        .unwrap()
+        .amount;
+
+    assert_eq!(external_rewards_amount, Uint128::new(4u128));
+}
+
+#[test]
+fn proper_auto_fund_stakers_failing_sink() {
+    let mut router = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+
+    let default_config: Config = Config {
+        auto_fund_stakers: true,
+        /* No contract lives there, so funding always fails */
+        reward_sink: Some(RewardSink::Cw20Stake {
+            addr: Addr::unchecked("missing_sink"),
+            fund_msg: to_binary(&StakeCw20ReceiveMsg::Fund {}).unwrap(),
+        }),
+        ..base_config()
+    };
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        Some(default_config),
+        owner.clone(),
+        funds,
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            prediction_market_addr.clone(),
+            &ExecuteMsg::BetBull {
+                round_id: Uint128::zero(),
+                referrer: None,
+                min_multiplier: None,
+                conditional: false,
+            },
+            &coins(100, SETTLE_DENOM),
+        )
+        .unwrap();
+
+    /* The round still goes live and the fee waits for the next attempt */
+    start_next_round(&mut router, &prediction_market_addr, &owner);
+
+    let status: StatusResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+    assert_eq!(status.live_round.unwrap().id, Uint128::zero());
+
+    let accumulated: AccumulatedFeesResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr, &QueryMsg::AccumulatedFees {})
+        .unwrap();
+    assert_eq!(accumulated.amount, Uint128::new(2u128));
+}
+
+#[test]
+fn proper_fund_stakers_reward_sinks() {
+    let mut router = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+    let treasury = Addr::unchecked("treasury");
+
+    let default_config: Config = Config {
+        reward_sink: Some(RewardSink::Address {
+            addr: treasury.clone(),
+        }),
+        ..base_config()
+    };
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        Some(default_config),
+        owner.clone(),
+        funds,
+    );
+
+    let config: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+
+    let bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBull {
+            round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: vec![Coin {
+            denom: SETTLE_DENOM.to_string(),
+            amount: Uint128::new(100u128),
+        }],
+    });
+
+    router
+        .execute_multi(owner.clone(), [bet_msg].to_vec())
+        .unwrap();
+
+    let fund_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::FundStakers {}).unwrap(),
+        funds: vec![],
+    });
+
+    router
+        .execute_multi(owner.clone(), [fund_msg.clone()].to_vec())
+        .unwrap();
+
+    let treasury_amount = router
+        .wrap()
+        .query_balance(treasury, SETTLE_DENOM)
+        .unwrap()
+        .amount;
+
+    assert_eq!(treasury_amount, Uint128::new(2u128));
+
+    // Switch to a staking contract with its own fund message
+    let update_config: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::UpdateConfig {
+            config: PartialConfig {
+                reward_sink: Some(Some(RewardSink::Cw20Stake {
+                    addr: config.cw20_stake_external_rewards_addr.clone(),
+                    fund_msg: to_binary(&StakeCw20ReceiveMsg::Fund {}).unwrap(),
+                })),
+                ..empty_partial_config()
+            },
+        })
+        .unwrap(),
+        funds: vec![],
+    });
+
+    router
+        .execute_multi(owner.clone(), [update_config].to_vec())
+        .unwrap();
+
+    start_next_round(&mut router, &prediction_market_addr, &owner);
+
+    let bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBear {
+            round_id: Uint128::new(1u128),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: vec![Coin {
+            denom: SETTLE_DENOM.to_string(),
+            amount: Uint128::new(100u128),
+        }],
+    });
+
+    router
+        .execute_multi(owner.clone(), [bet_msg].to_vec())
+        .unwrap();
+
+    router
+        .execute_multi(owner.clone(), [fund_msg].to_vec())
+        .unwrap();
+
+    let external_rewards_amount = router
+        .wrap()
+        .query_balance(config.cw20_stake_external_rewards_addr, SETTLE_DENOM)
+        .unwrap()
+        .amount;
+
+    assert_eq!(external_rewards_amount, Uint128::new(2u128));
+}
+
+#[test]
+fn proper_referral_fees() {
+    let mut router = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+    let frontend = Addr::unchecked("frontend");
+
+    let default_config: Config = Config {
+        referral_share: Uint128::new(5000u128),
+        ..base_config()
+    };
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        Some(default_config),
+        owner.clone(),
+        funds,
+    );
+
+    let self_referred_bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBull {
+            round_id: Uint128::zero(),
+            referrer: Some(owner.to_string()),
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: vec![Coin {
+            denom: SETTLE_DENOM.to_string(),
+            amount: Uint128::new(1000u128),
+        }],
+    });
+
+    router
+        .execute_multi(owner.clone(), [self_referred_bet_msg].to_vec())
+        .expect_err("Should not be able to refer your own bet");
+
+    let bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBull {
+            round_id: Uint128::zero(),
+            referrer: Some(frontend.to_string()),
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: vec![Coin {
+            denom: SETTLE_DENOM.to_string(),
+            amount: Uint128::new(1000u128),
+        }],
+    });
+
+    router
+        .execute_multi(owner.clone(), [bet_msg].to_vec())
+        .unwrap();
+
+    let referral: ReferrerResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr.clone(),
+            &QueryMsg::Referrer {
+                address: frontend.to_string(),
+            },
+        )
+        .unwrap();
+
+    // Half of the 2% staker fee
+    assert_eq!(referral.unclaimed, Uint128::new(10u128));
+    assert_eq!(referral.total_earned, Uint128::new(10u128));
+    assert_eq!(referral.volume, Uint128::new(1000u128));
+    assert_eq!(referral.bets, 1u64);
+
+    let accumulated: AccumulatedFeesResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr.clone(),
+            &QueryMsg::AccumulatedFees {},
+        )
+        .unwrap();
+
+    assert_eq!(accumulated.amount, Uint128::new(10u128));
+
+    let claim_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::ClaimReferralFees {}).unwrap(),
+        funds: vec![],
+    });
+
+    router
+        .execute_multi(frontend.clone(), [claim_msg.clone()].to_vec())
+        .unwrap();
+
+    let frontend_amount = router
+        .wrap()
+        .query_balance(frontend.clone(), SETTLE_DENOM)
+        .unwrap()
+        .amount;
+
+    assert_eq!(frontend_amount, Uint128::new(10u128));
+
+    router
+        .execute_multi(frontend.clone(), [claim_msg].to_vec())
+        .expect_err("Should not be able to claim referral fees twice");
+
+    let referral: ReferrerResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr,
+            &QueryMsg::Referrer {
+                address: frontend.to_string(),
+            },
+        )
+        .unwrap();
+
+    assert_eq!(referral.unclaimed, Uint128::zero());
+    assert_eq!(referral.total_earned, Uint128::new(10u128));
+}
+
+#[test]
+fn proper_fee_tiers() {
+    let mut router = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+    let staker = Addr::unchecked("staker");
+    let nobody = Addr::unchecked("nobody");
+
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &staker, coins(2000, SETTLE_DENOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &nobody, coins(2000, SETTLE_DENOM))
+            .unwrap()
+    });
+
+    let default_config: Config = Config {
+        fee_tiers: vec![
+            FeeTier {
+                name: "silver".to_string(),
+                min_staked: Uint128::new(100u128),
+                burn_fee: Uint128::new(100u128),
+                staker_fee: Uint128::new(100u128),
+            },
+            FeeTier {
+                name: "gold".to_string(),
+                min_staked: Uint128::new(1000u128),
+                burn_fee: Uint128::zero(),
+                staker_fee: Uint128::new(100u128),
+            },
+        ],
+        fee_tier_whitelist: vec![owner.clone()],
+        fee_tier_staking_addr: Some(Addr::unchecked("external_rewards")),
+        ..base_config()
+    };
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        Some(default_config),
+        owner.clone(),
+        funds,
+    );
+
+    let bet = |round_id: Uint128| -> CosmosMsg {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: prediction_market_addr.to_string(),
+            msg: to_binary(&ExecuteMsg::BetBull {
+                round_id,
+                referrer: None,
+                min_multiplier: None,
+                conditional: false,
+            })
+            .unwrap(),
+            funds: vec![Coin {
+                denom: SETTLE_DENOM.to_string(),
+                amount: Uint128::new(1000u128),
+            }],
+        })
+    };
+
+    // Whitelisted: gold tier, 1% fee
+    router
+        .execute_multi(owner.clone(), [bet(Uint128::zero())].to_vec())
+        .unwrap();
+
+    // 500 staked: silver tier, 2% fee
+    router
+        .execute_multi(staker.clone(), [bet(Uint128::zero())].to_vec())
+        .unwrap();
+
+    // Nothing staked: base fees, 3%
+    router
+        .execute_multi(nobody.clone(), [bet(Uint128::zero())].to_vec())
+        .unwrap();
+
+    // Quotes apply the same tier as the bet
+    let simulated: SimulateBetResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr.clone(),
+            &QueryMsg::SimulateBet {
+                round_id: Uint128::zero(),
+                direction: Direction::Bull,
+                amount: Uint128::new(1000u128),
+                address: Some(staker.to_string()),
+            },
+        )
+        .unwrap();
+    assert_eq!(simulated.burn_fee, Uint128::new(10u128));
+    assert_eq!(simulated.staker_fee, Uint128::new(10u128));
+    assert_eq!(simulated.net_amount, Uint128::new(980u128));
+
+    let status: StatusResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+
+    assert_eq!(
+        status.bidding_round.unwrap().bull_amount,
+        Uint128::new(990u128 + 980u128 + 970u128)
+    );
+
+    let burned_amount = router
+        .wrap()
+        .query_balance("burn", SETTLE_DENOM)
+        .unwrap()
+        .amount;
+
+    assert_eq!(burned_amount, Uint128::new(20u128));
+}
+
+#[test]
+fn proper_odds_and_simulate_bet() {
+    let mut router = mock_app();
+
+    let bull = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+
+    let bear = Addr::unchecked("loser");
+    let bear_funds = coins(2000, SETTLE_DENOM);
+
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &bear, bear_funds)
+            .unwrap()
+    });
+
+    let prediction_market_addr =
+        create_market_and_start(router.borrow_mut(), None, bull.clone(), funds);
+
+    let bull_bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBull {
+            round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: vec![Coin {
+            denom: SETTLE_DENOM.to_string(),
+            amount: Uint128::new(100u128),
+        }],
+    });
+
+    router
+        .execute_multi(bull.clone(), [bull_bet_msg].to_vec())
+        .unwrap();
+
+    let bear_bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBear {
+            round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: vec![Coin {
+            denom: SETTLE_DENOM.to_string(),
+            amount: Uint128::new(300u128),
+        }],
+    });
+
+    router
+        .execute_multi(bear.clone(), [bear_bet_msg].to_vec())
+        .unwrap();
+
+    // 97 bull vs 291 bear
+    let odds: OddsResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr.clone(),
+            &QueryMsg::Odds {
+                round_id: Uint128::zero(),
+            },
+        )
+        .unwrap();
+
+    assert_eq!(odds.bull_multiplier, Decimal::from_ratio(4u128, 1u128));
+    assert_eq!(odds.bear_multiplier, Decimal::from_ratio(388u128, 291u128));
+
+    let simulated: SimulateBetResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr.clone(),
+            &QueryMsg::SimulateBet {
+                round_id: Uint128::zero(),
+                direction: Direction::Bull,
+                amount: Uint128::new(100u128),
+                address: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(simulated.net_amount, Uint128::new(97u128));
+    assert_eq!(simulated.multiplier, Decimal::from_ratio(5u128, 2u128));
+    assert_eq!(simulated.payout, Uint128::new(242u128));
+
+    start_next_round(&mut router, &prediction_market_addr, &bull);
+
+    // Still quoted while live, but no longer open for bets
+    let odds: OddsResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr.clone(),
+            &QueryMsg::Odds {
+                round_id: Uint128::zero(),
+            },
+        )
+        .unwrap();
+
+    assert_eq!(odds.bull_multiplier, Decimal::from_ratio(4u128, 1u128));
+
+    router
+        .wrap()
+        .query_wasm_smart::<SimulateBetResponse>(
+            prediction_market_addr.clone(),
+            &QueryMsg::SimulateBet {
+                round_id: Uint128::zero(),
+                direction: Direction::Bull,
+                amount: Uint128::new(100u128),
+                address: None,
+            },
+        )
+        .expect_err("Should not be able to simulate a bet on a live round");
+
+    // Empty bidding round refunds, so it pays 1:1
+    let odds: OddsResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr,
+            &QueryMsg::Odds {
+                round_id: Uint128::new(1u128),
+            },
+        )
+        .unwrap();
+
+    assert_eq!(odds.bull_multiplier, Decimal::one());
+    assert_eq!(odds.bear_multiplier, Decimal::one());
+}
+
+#[test]
+fn proper_min_multiplier_and_conditional_bets() {
+    let mut router = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+    let bear = Addr::unchecked("bear");
+    let late_bear = Addr::unchecked("late_bear");
+    let conditional_bear = Addr::unchecked("conditional_bear");
+    let conditional_bull = Addr::unchecked("conditional_bull");
+
+    router.borrow_mut().init_modules(|router, _, storage| {
+        for account in [&bear, &late_bear, &conditional_bear, &conditional_bull]
+        {
+            router
+                .bank
+                .init_balance(storage, account, coins(2000, SETTLE_DENOM))
+                .unwrap();
+        }
+    });
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        None,
+        owner.clone(),
+        funds,
+    );
+
+    let bet = |dir: Direction,
+               min_multiplier: Option<Decimal>,
+               conditional: bool|
+     -> CosmosMsg {
+        let round_id = Uint128::zero();
+        let msg = match dir {
+            Direction::Bull => ExecuteMsg::BetBull {
+                round_id,
+                referrer: None,
+                min_multiplier,
+                conditional,
+            },
+            Direction::Bear => ExecuteMsg::BetBear {
+                round_id,
+                referrer: None,
+                min_multiplier,
+                conditional,
+            },
+        };
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: prediction_market_addr.to_string(),
+            msg: to_binary(&msg).unwrap(),
+            funds: vec![Coin {
+                denom: SETTLE_DENOM.to_string(),
+                amount: Uint128::new(100u128),
+            }],
+        })
+    };
+
+    router
+        .execute_multi(
+            owner.clone(),
+            [bet(Direction::Bull, None, false)].to_vec(),
+        )
+        .unwrap();
+
+    // 97 vs 97 pays 2x
+    router
+        .execute_multi(
+            bear.clone(),
+            [bet(
+                Direction::Bear,
+                Some(Decimal::from_ratio(2u128, 1u128)),
+                false,
+            )]
+            .to_vec(),
+        )
+        .unwrap();
+
+    // 97 vs 194 would only pay 1.5x
+    router
+        .execute_multi(
+            late_bear.clone(),
+            [bet(
+                Direction::Bear,
+                Some(Decimal::from_ratio(2u128, 1u128)),
+                false,
+            )]
+            .to_vec(),
+        )
+        .expect_err("Should not be able to bet below the minimum multiplier");
+
+    router
+        .execute_multi(
+            conditional_bear.clone(),
+            [bet(Direction::Bear, None, true)].to_vec(),
+        )
+        .expect_err("Should not be able to bet conditionally without a limit");
+
+    router
+        .execute_multi(
+            conditional_bear.clone(),
+            [bet(
+                Direction::Bear,
+                Some(Decimal::from_ratio(14u128, 10u128)),
+                true,
+            )]
+            .to_vec(),
+        )
+        .unwrap();
+
+    router
+        .execute_multi(
+            conditional_bull.clone(),
+            [bet(
+                Direction::Bull,
+                Some(Decimal::from_ratio(3u128, 1u128)),
+                true,
+            )]
+            .to_vec(),
+        )
+        .unwrap();
+
+    // Escrowed bets are not counted yet
+    let status: StatusResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+
+    let bidding_round = status.bidding_round.unwrap();
+    assert_eq!(bidding_round.bull_amount, Uint128::new(97u128));
+    assert_eq!(bidding_round.bear_amount, Uint128::new(97u128));
+
+    start_next_round(&mut router, &prediction_market_addr, &owner);
+
+    // The bull needs 3x but only gets 2x, once refunded the bear still
+    // gets 1.5x which is above its 1.4x limit
+    let status: StatusResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+
+    let live_round = status.live_round.unwrap();
+    assert_eq!(live_round.bull_amount, Uint128::new(97u128));
+    assert_eq!(live_round.bear_amount, Uint128::new(194u128));
+
+    let conditional_bull_balance = router
+        .wrap()
+        .query_balance(conditional_bull, SETTLE_DENOM)
+        .unwrap()
+        .amount;
+
+    assert_eq!(conditional_bull_balance, Uint128::new(1997u128));
+}
+
+#[test]
+fn proper_solvency() {
+    let mut router = mock_app();
+
+    let winner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+
+    let loser = Addr::unchecked("loser");
+    let loser_funds = coins(2000, SETTLE_DENOM);
+
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &loser, loser_funds)
+            .unwrap()
+    });
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        None,
+        winner.clone(),
+        funds,
+    );
+
+    let config: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+
+    let loser_bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBear {
+            round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: vec![Coin {
+            denom: SETTLE_DENOM.to_string(),
+            amount: Uint128::new(100u128),
+        }],
+    });
+
+    router
+        .execute_multi(loser.clone(), [loser_bet_msg].to_vec())
+        .unwrap();
+
+    let bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBull {
+            round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: vec![Coin {
+            denom: SETTLE_DENOM.to_string(),
+            amount: Uint128::new(100u128),
+        }],
+    });
+
+    router
+        .execute_multi(winner.clone(), [bet_msg].to_vec())
+        .unwrap();
+
+    // 2 burned, 194 in the pool and 4 for the stakers
+    let solvency: SolvencyResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr.clone(),
+            &QueryMsg::Solvency {},
+        )
+        .unwrap();
+
+    assert_eq!(solvency.balance, Uint128::new(198u128));
+    assert_eq!(solvency.bet_liability, Uint128::new(194u128));
+    assert_eq!(solvency.accumulated_fee, Uint128::new(4u128));
+    assert_eq!(solvency.total_liabilities, Uint128::new(198u128));
+    assert_eq!(solvency.deficit, Uint128::zero());
+
+    start_next_round(&mut router, &prediction_market_addr, &winner);
+    update_price(
+        &mut router,
+        config.clone(),
+        Uint128::new(1_000_001u128),
+        &winner,
+    );
+    start_next_round(&mut router, &prediction_market_addr, &winner);
+
+    let claim_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::CollectWinnings {
+            rounds: vec![Uint128::from(0u128)],
+        })
+        .unwrap(),
+        funds: vec![],
+    });
+
+    router
+        .execute_multi(winner.clone(), [claim_msg].to_vec())
+        .unwrap();
+
+    let fund_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::FundStakers {}).unwrap(),
+        funds: vec![],
+    });
+
+    router
+        .execute_multi(winner.clone(), [fund_msg].to_vec())
+        .unwrap();
+
+    let solvency: SolvencyResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr, &QueryMsg::Solvency {})
+        .unwrap();
+
+    assert_eq!(solvency.balance, Uint128::zero());
+    assert_eq!(solvency.total_liabilities, Uint128::zero());
+    assert_eq!(solvency.surplus, Uint128::zero());
+    assert_eq!(solvency.deficit, Uint128::zero());
+}
+
+#[test]
+fn proper_sweep_dust() {
+    let mut router = mock_app();
+
+    let winner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+
+    let other_winner = Addr::unchecked("other_winner");
+    let loser = Addr::unchecked("loser");
+
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &loser, coins(2000, SETTLE_DENOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &other_winner, coins(2000, SETTLE_DENOM))
+            .unwrap()
+    });
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        None,
+        winner.clone(),
+        funds,
+    );
+
+    let config: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+
+    let bet = |msg: ExecuteMsg, amount: u128| -> CosmosMsg {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: prediction_market_addr.to_string(),
+            msg: to_binary(&msg).unwrap(),
+            funds: coins(amount, SETTLE_DENOM),
+        })
+    };
+
+    /* 97 bear against 97 + 194 bull, so neither payout is exact */
+    router
+        .execute_multi(
+            loser.clone(),
+            vec![bet(
+                ExecuteMsg::BetBear {
+                    round_id: Uint128::zero(),
+                    referrer: None,
+                    min_multiplier: None,
+                    conditional: false,
+                },
+                100u128,
+            )],
+        )
+        .unwrap();
+    router
+        .execute_multi(
+            winner.clone(),
+            vec![bet(
+                ExecuteMsg::BetBull {
+                    round_id: Uint128::zero(),
+                    referrer: None,
+                    min_multiplier: None,
+                    conditional: false,
+                },
+                100u128,
+            )],
+        )
+        .unwrap();
+    router
+        .execute_multi(
+            other_winner.clone(),
+            vec![bet(
+                ExecuteMsg::BetBull {
+                    round_id: Uint128::zero(),
+                    referrer: None,
+                    min_multiplier: None,
+                    conditional: false,
+                },
+                200u128,
+            )],
+        )
+        .unwrap();
+
+    start_next_round(&mut router, &prediction_market_addr, &winner);
+    update_price(
+        &mut router,
+        config.clone(),
+        Uint128::new(1_000_001u128),
+        &winner,
+    );
+    start_next_round(&mut router, &prediction_market_addr, &winner);
+
+    let claim = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::CollectWinnings {
+            rounds: vec![Uint128::zero()],
+        })
+        .unwrap(),
+        funds: vec![],
+    });
+    let sweep = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::SweepDust {
+            round_ids: vec![Uint128::zero()],
+        })
+        .unwrap(),
+        funds: vec![],
+    });
+
+    router
+        .execute_multi(winner.clone(), vec![claim.clone()])
+        .unwrap();
+
+    /* Unknown until every winner has claimed */
+    let payouts: RoundPayoutsResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr.clone(),
+            &QueryMsg::RoundPayouts {
+                round_id: Uint128::zero(),
+            },
+        )
+        .unwrap();
+    assert_eq!(payouts.winning_shares, Uint128::new(291u128));
+    assert_eq!(payouts.paid_out, Uint128::new(129u128));
+    assert_eq!(payouts.dust, None);
+    router
+        .execute_multi(loser.clone(), vec![sweep.clone()])
+        .unwrap_err();
+
+    router
+        .execute_multi(other_winner.clone(), vec![claim])
+        .unwrap();
+
+    let payouts: RoundPayoutsResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr.clone(),
+            &QueryMsg::RoundPayouts {
+                round_id: Uint128::zero(),
+            },
+        )
+        .unwrap();
+    assert_eq!(payouts.paid_out, Uint128::new(387u128));
+    assert_eq!(payouts.dust, Some(Uint128::new(1u128)));
+
+    /* Anyone can sweep, but only once */
+    router
+        .execute_multi(loser.clone(), vec![sweep.clone()])
+        .unwrap();
+    router.execute_multi(loser, vec![sweep]).unwrap_err();
+
+    let fees: AccumulatedFeesResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr.clone(),
+            &QueryMsg::AccumulatedFees {},
+        )
+        .unwrap();
+    assert_eq!(fees.amount, Uint128::new(9u128));
+
+    let solvency: SolvencyResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr, &QueryMsg::Solvency {})
+        .unwrap();
+    assert_eq!(solvency.bet_liability, Uint128::zero());
+    assert_eq!(solvency.surplus, Uint128::zero());
+}
+
+#[test]
+fn proper_sweep_expired() {
+    let mut router = mock_app();
+
+    let winner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+
+    let loser = Addr::unchecked("loser");
+
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &loser, coins(2000, SETTLE_DENOM))
+            .unwrap()
+    });
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        Some(Config {
+            claim_expiry_rounds: Uint128::new(1u128),
+            ..base_config()
+        }),
+        winner.clone(),
+        funds,
+    );
+
+    let config: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+
+    let loser_bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBear {
+            round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: coins(100, SETTLE_DENOM),
+    });
+    router
+        .execute_multi(loser.clone(), vec![loser_bet_msg])
+        .unwrap();
+
+    let bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBull {
+            round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: coins(100, SETTLE_DENOM),
+    });
+    router.execute_multi(winner.clone(), vec![bet_msg]).unwrap();
+
+    start_next_round(&mut router, &prediction_market_addr, &winner);
+    update_price(
+        &mut router,
+        config.clone(),
+        Uint128::new(1_000_001u128),
+        &winner,
+    );
+    start_next_round(&mut router, &prediction_market_addr, &winner);
+
+    let sweep_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::SweepExpired {
+            round_ids: vec![Uint128::zero()],
+        })
+        .unwrap(),
+        funds: vec![],
+    });
+
+    /* Still claimable until the next round finishes */
+    router
+        .execute_multi(loser.clone(), vec![sweep_msg.clone()])
+        .unwrap_err();
+
+    start_next_round(&mut router, &prediction_market_addr, &winner);
+
+    let claim_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::CollectWinnings {
+            rounds: vec![Uint128::zero()],
+        })
+        .unwrap(),
+        funds: vec![],
+    });
+    router
+        .execute_multi(winner.clone(), vec![claim_msg])
+        .unwrap_err();
+
+    router
+        .execute_multi(loser.clone(), vec![sweep_msg.clone()])
+        .unwrap();
+    router.execute_multi(loser, vec![sweep_msg]).unwrap_err();
+
+    let payouts: RoundPayoutsResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr.clone(),
+            &QueryMsg::RoundPayouts {
+                round_id: Uint128::zero(),
+            },
+        )
+        .unwrap();
+    assert!(payouts.expired);
+    assert_eq!(payouts.unclaimed_swept, Uint128::new(194u128));
+
+    let fees: AccumulatedFeesResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr.clone(),
+            &QueryMsg::AccumulatedFees {},
+        )
+        .unwrap();
+    assert_eq!(fees.amount, Uint128::new(198u128));
+
+    let solvency: SolvencyResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr.clone(),
+            &QueryMsg::Solvency {},
+        )
+        .unwrap();
+    assert_eq!(solvency.bet_liability, Uint128::zero());
+    assert_eq!(solvency.deficit, Uint128::zero());
+
+    /* Nothing is owed anymore, so Prune removes the expired bets */
+    let prune_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::Prune {
+            before_round: Uint128::new(1u128),
+            start_after: None,
+            limit: None,
+        })
+        .unwrap(),
+        funds: vec![],
+    });
+    router.execute_multi(winner, vec![prune_msg]).unwrap();
+    router
+        .wrap()
+        .query_wasm_smart::<RoundResponse>(
+            prediction_market_addr,
+            &QueryMsg::FinishedRound {
+                round_id: Uint128::zero(),
+            },
+        )
+        .unwrap_err();
+}
+
+#[test]
+fn proper_prune() {
+    let mut router = mock_app();
+
+    let winner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+
+    let loser = Addr::unchecked("loser");
+
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &loser, coins(2000, SETTLE_DENOM))
+            .unwrap()
+    });
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        None,
+        winner.clone(),
+        funds,
+    );
+
+    let config: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+
+    let loser_bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBear {
+            round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: coins(100, SETTLE_DENOM),
+    });
+    router
+        .execute_multi(loser.clone(), vec![loser_bet_msg])
+        .unwrap();
+
+    let bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBull {
+            round_id: Uint128::zero(),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: coins(100, SETTLE_DENOM),
+    });
+    router.execute_multi(winner.clone(), vec![bet_msg]).unwrap();
+
+    start_next_round(&mut router, &prediction_market_addr, &winner);
+    update_price(
+        &mut router,
+        config.clone(),
+        Uint128::new(1_000_001u128),
+        &winner,
+    );
+    start_next_round(&mut router, &prediction_market_addr, &winner);
+
+    let prune = |limit: Option<u32>| -> CosmosMsg {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: prediction_market_addr.to_string(),
+            msg: to_binary(&ExecuteMsg::Prune {
+                before_round: Uint128::new(1u128),
+                start_after: None,
+                limit,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    };
+
+    /* The winner has not claimed yet, the round is kept */
+    router
+        .execute_multi(loser.clone(), vec![prune(None)])
+        .unwrap();
+    router
+        .wrap()
+        .query_wasm_smart::<RoundResponse>(
+            prediction_market_addr.clone(),
+            &QueryMsg::FinishedRound {
+                round_id: Uint128::zero(),
+            },
+        )
+        .unwrap();
+
+    let claim_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::CollectWinnings {
+            rounds: vec![Uint128::zero()],
+        })
+        .unwrap(),
+        funds: vec![],
+    });
+    router
+        .execute_multi(winner.clone(), vec![claim_msg])
+        .unwrap();
+
+    /* Only enough budget for the round and the losing bet */
+    router
+        .execute_multi(loser.clone(), vec![prune(Some(2))])
+        .unwrap();
+    router
+        .wrap()
+        .query_wasm_smart::<RoundResponse>(
+            prediction_market_addr.clone(),
+            &QueryMsg::FinishedRound {
+                round_id: Uint128::zero(),
+            },
+        )
+        .unwrap();
+
+    router
+        .execute_multi(loser.clone(), vec![prune(None)])
+        .unwrap();
+    router.execute_multi(loser, vec![prune(None)]).unwrap_err();
+
+    router
+        .wrap()
+        .query_wasm_smart::<RoundResponse>(
+            prediction_market_addr.clone(),
+            &QueryMsg::FinishedRound {
+                round_id: Uint128::zero(),
+            },
+        )
+        .unwrap_err();
+
+    let summary: RoundSummaryResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr,
+            &QueryMsg::RoundSummary {
+                round_id: Uint128::zero(),
+            },
+        )
+        .unwrap();
+    assert_eq!(summary.close_price, Uint128::new(1_000_001u128));
+    assert_eq!(summary.winner.unwrap().to_string(), "bull");
+    assert_eq!(summary.bull_amount, Uint128::new(97u128));
+    assert_eq!(summary.bear_amount, Uint128::new(97u128));
+}
+
+#[test]
+fn proper_prune_skips_unclaimed() {
+    let mut deps = mock_dependencies();
+
+    /* Two rounds still owe their winnings, the third is settled */
+    for round_id in 0..3u128 {
+        let pool = if round_id < 2 { 100u128 } else { 0u128 };
+        ROUNDS
+            .save(
+                deps.as_mut().storage,
+                round_id,
+                &FinishedRound {
+                    id: Uint128::new(round_id),
+                    bid_time: Timestamp::from_seconds(0),
+                    open_time: Timestamp::from_seconds(0),
+                    close_time: Timestamp::from_seconds(0),
+                    open_price: Uint128::new(1u128),
+                    close_price: Uint128::new(2u128),
+                    winner: Some(Direction::Bull),
+                    bull_amount: Uint128::new(pool),
+                    bear_amount: Uint128::zero(),
+                    open_price_time: None,
+                    close_price_time: None,
+                    voided: false,
+                    price_expo: 0,
+                },
+            )
+            .unwrap();
+    }
+
+    let prune = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>,
+                 start_after: Option<u128>| {
+        crate::contract::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::Prune {
+                before_round: Uint128::new(3u128),
+                start_after: start_after.map(Uint128::new),
+                limit: Some(1),
+            },
+        )
+    };
+
+    /* The kept rounds do not use up the limit */
+    let resp = prune(&mut deps, None).unwrap();
+    assert!(ARCHIVED_ROUNDS.has(&deps.storage, 2u128));
+    assert!(ROUNDS.has(&deps.storage, 0u128));
+    assert!(ROUNDS.has(&deps.storage, 1u128));
+    assert!(resp
+        .attributes
+        .iter()
+        .any(|a| a.key == "prune_start_after" && a.value == "2"));
+
+    /* Only kept rounds left, the cursor still moves past them */
+    let resp = prune(&mut deps, None).unwrap();
+    assert!(resp.events.is_empty());
+    assert!(resp
+        .attributes
+        .iter()
+        .any(|a| a.key == "prune_start_after" && a.value == "1"));
+
+    prune(&mut deps, Some(2)).unwrap_err();
+}
+
+#[test]
+fn proper_migrate_legacy_bets() {
+    let mut deps = mock_dependencies();
+    let bull = Addr::unchecked("bull");
+    let bear = Addr::unchecked("bear");
+
+    cw2::set_contract_version(
+        deps.as_mut().storage,
+        "deliverdao:price_prediction",
+        "0.1.0",
+    )
+    .unwrap();
+    LEGACY_BULL_BETS
+        .save(deps.as_mut().storage, (0u128, bull.clone()), &97u128)
+        .unwrap();
+    LEGACY_BEAR_BETS
+        .save(deps.as_mut().storage, (0u128, bear.clone()), &194u128)
+        .unwrap();
+    LEGACY_MY_CLAIMED_ROUNDS
+        .save(deps.as_mut().storage, (bull.clone(), 1u128), &true)
+        .unwrap();
+
+    crate::contract::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg { config: None },
+    )
+    .unwrap();
+
+    /* Everything is moved in batches, nothing is accepted meanwhile */
+    let migrate_legacy = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>,
+                          limit| {
+        crate::contract::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::MigrateLegacy { limit },
+        )
+    };
+    crate::contract::execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::CollectWinnings {
+            rounds: vec![Uint128::zero()],
+        },
+    )
+    .unwrap_err();
+    assert!(LEGACY_BULL_BETS.has(&deps.storage, (0u128, bull.clone())));
+
+    migrate_legacy(&mut deps, Some(1)).unwrap();
+    assert!(!LEGACY_BULL_BETS.has(&deps.storage, (0u128, bull.clone())));
+    assert!(LEGACY_BEAR_BETS.has(&deps.storage, (0u128, bear.clone())));
+
+    migrate_legacy(&mut deps, Some(2)).unwrap();
+    assert!(LEGACY_MIGRATION.may_load(&deps.storage).unwrap().is_some());
+    migrate_legacy(&mut deps, None).unwrap();
+    assert!(LEGACY_MIGRATION.may_load(&deps.storage).unwrap().is_none());
+    migrate_legacy(&mut deps, None).unwrap_err();
+
+    let bull_bet = BETS.load(&deps.storage, (0u128, bull.clone())).unwrap();
+    assert_eq!(bull_bet.direction, Direction::Bull);
+    assert_eq!(bull_bet.amount, Uint128::new(97u128));
+    assert_eq!(bull_bet.placed_at, mock_env().block.time);
+    assert!(!bull_bet.claimed);
+
+    let bear_bet = BETS.load(&deps.storage, (0u128, bear.clone())).unwrap();
+    assert_eq!(bear_bet.direction, Direction::Bear);
+    assert_eq!(bear_bet.amount, Uint128::new(194u128));
+
+    assert!(!LEGACY_BULL_BETS.has(&deps.storage, (0u128, bull.clone())));
+    assert!(!LEGACY_BEAR_BETS.has(&deps.storage, (0u128, bear)));
+    assert!(!LEGACY_MY_CLAIMED_ROUNDS.has(&deps.storage, (bull, 1u128)));
+
+    /* Neither round has finished, so both bets are owed in full */
+    let liabilities = LIABILITIES.load(&deps.storage).unwrap();
+    assert_eq!(liabilities.bets, Uint128::new(291u128));
+}
+
+#[test]
+fn proper_migrate_versions() {
+    let mut deps = mock_dependencies();
+
+    let default_config: Config = base_config();
+    CONFIG.save(deps.as_mut().storage, &default_config).unwrap();
+
+    cw2::set_contract_version(
+        deps.as_mut().storage,
+        "deliverdao:price_prediction",
+        "99.0.0",
+    )
+    .unwrap();
+    crate::contract::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg { config: None },
+    )
+    .unwrap_err();
+
+    cw2::set_contract_version(
+        deps.as_mut().storage,
+        "deliverdao:price_prediction",
+        env!("CARGO_PKG_VERSION"),
+    )
+    .unwrap();
+    crate::contract::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            config: Some(PartialConfig {
+                minimum_bet: Some(Uint128::new(10u128)),
+                ..empty_partial_config()
+            }),
+        },
+    )
+    .unwrap();
+
+    let config = CONFIG.load(&deps.storage).unwrap();
+    assert_eq!(config.minimum_bet, Uint128::new(10u128));
+    assert_eq!(config.staker_fee, default_config.staker_fee);
+}
+
+#[test]
+fn proper_sudo_rounds() {
+    let mut router = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        None,
+        owner.clone(),
+        funds,
+    );
+
+    /* Nothing is due yet, the clock must not fail */
+    router
+        .wasm_sudo(prediction_market_addr.clone(), &SudoMsg::ClockEndBlock {})
+        .unwrap();
+
+    router.update_block(|block| {
+        block.time = block.time.plus_seconds(600);
+        block.height += 1;
+    });
+    router
+        .wasm_sudo(prediction_market_addr.clone(), &SudoMsg::Tick {})
+        .unwrap();
+
+    let status: StatusResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+    assert_eq!(status.live_round.unwrap().id, Uint128::zero());
+    assert_eq!(status.bidding_round.unwrap().id, Uint128::new(1u128));
+
+    router
+        .wasm_sudo(
+            prediction_market_addr.clone(),
+            &SudoMsg::ForceHalt { halted: true },
+        )
+        .unwrap();
+
+    let bet_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: prediction_market_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::BetBull {
+            round_id: Uint128::new(1u128),
+            referrer: None,
+            min_multiplier: None,
+            conditional: false,
+        })
+        .unwrap(),
+        funds: coins(100, SETTLE_DENOM),
+    });
+    router
+        .execute_multi(owner.clone(), vec![bet_msg])
+        .unwrap_err();
+
+    /* Haulted games are skipped by the clock */
+    router.update_block(|block| {
+        block.time = block.time.plus_seconds(600);
+        block.height += 1;
+    });
+    router
+        .wasm_sudo(prediction_market_addr.clone(), &SudoMsg::ClockEndBlock {})
+        .unwrap();
+
+    let status: StatusResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+    assert_eq!(status.live_round.unwrap().id, Uint128::zero());
+
+    router
+        .wasm_sudo(
+            prediction_market_addr.clone(),
+            &SudoMsg::ForceConfig {
+                config: PartialConfig {
+                    minimum_bet: Some(Uint128::new(10u128)),
+                    ..empty_partial_config()
+                },
+            },
+        )
+        .unwrap();
+
+    let config: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.minimum_bet, Uint128::new(10u128));
+}
+
+#[test]
+fn proper_commit_reveal_bets() {
+    let mut router = mock_app();
+
+    let winner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+
+    let loser = Addr::unchecked("loser");
+    let sniper = Addr::unchecked("sniper");
+
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &loser, coins(2000, SETTLE_DENOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sniper, coins(2000, SETTLE_DENOM))
+            .unwrap()
+    });
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        Some(Config {
+            minimum_bet: Uint128::new(10u128),
+            commit_reveal: true,
+            reveal_window_seconds: Uint128::new(300u128),
+            ..base_config()
+        }),
+        winner.clone(),
+        funds,
+    );
+
+    let config: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+
+    let execute = |msg: ExecuteMsg, funds: Vec<Coin>| -> CosmosMsg {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: prediction_market_addr.to_string(),
+            msg: to_binary(&msg).unwrap(),
+            funds,
+        })
+    };
+    let commit = |direction: Direction, account: &Addr| -> CosmosMsg {
+        execute(
+            ExecuteMsg::CommitBet {
+                round_id: Uint128::zero(),
+                hash: crate::contract::commitment_hash(
+                    Uint128::zero(),
+                    &direction,
+                    "salt",
+                    account,
+                ),
+                referrer: None,
+            },
+            coins(100, SETTLE_DENOM),
+        )
+    };
+    let reveal = |direction: Direction, salt: &str| -> CosmosMsg {
+        execute(
+            ExecuteMsg::RevealBet {
+                round_id: Uint128::zero(),
+                direction,
+                salt: salt.to_string(),
+            },
+            vec![],
+        )
+    };
+
+    /* Directions can only be committed */
+    router
+        .execute_multi(
+            winner.clone(),
+            vec![execute(
+                ExecuteMsg::BetBull {
+                    round_id: Uint128::zero(),
+                    referrer: None,
+                    min_multiplier: None,
+                    conditional: false,
+                },
+                coins(100, SETTLE_DENOM),
+            )],
+        )
+        .unwrap_err();
+
+    /* Commitments are held to the minimum bet too */
+    router
+        .execute_multi(
+            winner.clone(),
+            vec![execute(
+                ExecuteMsg::CommitBet {
+                    round_id: Uint128::zero(),
+                    hash: crate::contract::commitment_hash(
+                        Uint128::zero(),
+                        &Direction::Bull,
+                        "salt",
+                        &winner,
+                    ),
+                    referrer: None,
+                },
+                coins(5, SETTLE_DENOM),
+            )],
+        )
+        .unwrap_err();
+
+    router
+        .execute_multi(winner.clone(), vec![commit(Direction::Bull, &winner)])
+        .unwrap();
+    router
+        .execute_multi(loser.clone(), vec![commit(Direction::Bear, &loser)])
+        .unwrap();
+    router
+        .execute_multi(sniper.clone(), vec![commit(Direction::Bull, &sniper)])
+        .unwrap();
+
+    /* Nothing is revealed while bids are open */
+    router
+        .execute_multi(winner.clone(), vec![reveal(Direction::Bull, "salt")])
+        .unwrap_err();
+
+    start_next_round(&mut router, &prediction_market_addr, &winner);
+
+    router
+        .execute_multi(winner.clone(), vec![reveal(Direction::Bear, "salt")])
+        .unwrap_err();
+    router
+        .execute_multi(winner.clone(), vec![reveal(Direction::Bull, "pepper")])
+        .unwrap_err();
+    router
+        .execute_multi(winner.clone(), vec![reveal(Direction::Bull, "salt")])
+        .unwrap();
+    router
+        .execute_multi(loser.clone(), vec![reveal(Direction::Bear, "salt")])
+        .unwrap();
+
+    let odds: OddsResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr.clone(),
+            &QueryMsg::Odds {
+                round_id: Uint128::zero(),
+            },
+        )
+        .unwrap();
+    assert_eq!(odds.bull_amount, Uint128::new(97u128));
+    assert_eq!(odds.bear_amount, Uint128::new(97u128));
+
+    update_price(
+        &mut router,
+        config.clone(),
+        Uint128::new(1_000_001u128),
+        &winner,
+    );
+    start_next_round(&mut router, &prediction_market_addr, &winner);
+
+    /* The sniper's unrevealed bet went to the stakers */
+    let fees: AccumulatedFeesResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr.clone(),
+            &QueryMsg::AccumulatedFees {},
+        )
+        .unwrap();
+    assert_eq!(fees.amount, Uint128::new(103u128));
+
+    router
+        .execute_multi(
+            winner.clone(),
+            vec![execute(
+                ExecuteMsg::CollectWinnings {
+                    rounds: vec![Uint128::zero()],
+                },
+                vec![],
+            )],
+        )
+        .unwrap();
+
+    let solvency: SolvencyResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr, &QueryMsg::Solvency {})
+        .unwrap();
+    assert_eq!(solvency.escrow_liability, Uint128::zero());
+    assert_eq!(solvency.bet_liability, Uint128::zero());
+    assert_eq!(solvency.deficit, Uint128::zero());
+}
+
+#[test]
+fn proper_reveal_checks() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let bettor = Addr::unchecked("bettor");
+
+    crate::contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        InstantiateMsg {
+            config: Config {
+                commit_reveal: true,
+                reveal_window_seconds: Uint128::new(300u128),
+                ..base_config()
+            },
+            settle_denom: SETTLE_DENOM.to_string(),
+        },
+    )
+    .unwrap();
+
+    LIVE_ROUND
+        .save(
+            deps.as_mut().storage,
+            &LiveRound {
+                id: Uint128::zero(),
+                bid_time: env.block.time.minus_seconds(600),
+                open_time: env.block.time,
+                close_time: env.block.time.plus_seconds(600),
+                open_price: Uint128::new(1u128),
+                bull_amount: Uint128::zero(),
+                bear_amount: Uint128::zero(),
+                open_price_time: None,
+                price_expo: 0,
+            },
+        )
+        .unwrap();
+    COMMITMENTS
+        .save(
+            deps.as_mut().storage,
+            (0u128, bettor.clone()),
+            &Commitment {
+                hash: crate::contract::commitment_hash(
+                    Uint128::zero(),
+                    &Direction::Bull,
+                    "salt",
+                    &bettor,
+                ),
+                amount: Uint128::new(97u128),
+                gross_amount: Uint128::new(100u128),
+                committed_at: env.block.time.minus_seconds(60),
+            },
+        )
+        .unwrap();
+    LIABILITIES
+        .save(
+            deps.as_mut().storage,
+            &Liabilities {
+                escrow: Uint128::new(97u128),
+                ..Liabilities::default()
+            },
+        )
+        .unwrap();
+
+    let reveal = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>| {
+        crate::contract::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(bettor.as_str(), &[]),
+            ExecuteMsg::RevealBet {
+                round_id: Uint128::zero(),
+                direction: Direction::Bull,
+                salt: "salt".to_string(),
+            },
+        )
+    };
+
+    /* A bet placed while commit-reveal was off is not overwritten */
+    BETS.save(
+        deps.as_mut().storage,
+        (0u128, bettor.clone()),
+        &Bet {
+            direction: Direction::Bear,
+            amount: Uint128::new(50u128),
+            placed_at: env.block.time.minus_seconds(30),
+            claimed: false,
+        },
+    )
+    .unwrap();
+    reveal(&mut deps).unwrap_err();
+    BETS.remove(deps.as_mut().storage, (0u128, bettor.clone()));
+
+    /* The minimum bet was raised after the commitment */
+    CONFIG
+        .update(deps.as_mut().storage, |config| -> StdResult<_> {
+            Ok(Config {
+                minimum_bet: Uint128::new(200u128),
+                ..config
+            })
+        })
+        .unwrap();
+    reveal(&mut deps).unwrap_err();
+    CONFIG
+        .update(deps.as_mut().storage, |config| -> StdResult<_> {
+            Ok(Config {
+                minimum_bet: Uint128::new(1u128),
+                ..config
+            })
+        })
+        .unwrap();
+
+    reveal(&mut deps).unwrap();
+    let bet = BETS.load(&deps.storage, (0u128, bettor)).unwrap();
+    assert_eq!(bet.amount, Uint128::new(97u128));
+    assert_eq!(
+        LIVE_ROUND.load(&deps.storage).unwrap().bull_amount,
+        bet.amount
+    );
+}
+
+#[test]
+fn proper_anti_snipe_extension() {
+    let mut router = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+
+    let early = Addr::unchecked("early");
+    let sniper = Addr::unchecked("sniper");
+    let latecomer = Addr::unchecked("latecomer");
+
+    router.borrow_mut().init_modules(|router, _, storage| {
+        for account in [&early, &sniper, &latecomer] {
+            router
+                .bank
+                .init_balance(storage, account, coins(2000, SETTLE_DENOM))
+                .unwrap();
+        }
+    });
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        Some(Config {
+            snipe_window_seconds: Uint128::new(120u128),
+            /* 10% */
+            snipe_ratio_threshold: Uint128::new(1000u128),
+            snipe_extension_seconds: Uint128::new(60u128),
+            max_snipe_extension_seconds: Uint128::new(90u128),
+            /* 1% */
+            late_bet_surcharge: Uint128::new(100u128),
+            ..base_config()
+        }),
+        owner.clone(),
+        funds,
+    );
+
+    let bet = |msg: ExecuteMsg| -> CosmosMsg {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: prediction_market_addr.to_string(),
+            msg: to_binary(&msg).unwrap(),
+            funds: coins(100, SETTLE_DENOM),
+        })
+    };
+    let bet_bull = ExecuteMsg::BetBull {
+        round_id: Uint128::zero(),
+        referrer: None,
+        min_multiplier: None,
+        conditional: false,
+    };
+    let bet_bear = ExecuteMsg::BetBear {
+        round_id: Uint128::zero(),
+        referrer: None,
+        min_multiplier: None,
+        conditional: false,
+    };
+
+    let status: StatusResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+    let open_time = status.bidding_round.unwrap().open_time;
+
+    /* Not late, no surcharge and no extension */
+    router
+        .execute_multi(early, vec![bet(bet_bear.clone())])
+        .unwrap();
+
+    router.update_block(|block| {
+        block.time = block.time.plus_seconds(500);
+        block.height += 1;
+    });
+
+    /* Flips the pool from all bear to about even */
+    router.execute_multi(sniper, vec![bet(bet_bull)]).unwrap();
+
+    let status: StatusResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+    let bidding_round = status.bidding_round.unwrap();
+    assert_eq!(bidding_round.open_time, open_time.plus_seconds(60));
+    assert_eq!(bidding_round.bull_amount, Uint128::new(96u128));
+
+    /* Past the original open_time, but bids are still accepted */
+    router.update_block(|block| {
+        block.time = block.time.plus_seconds(150);
+        block.height += 1;
+    });
+    router
+        .execute_multi(latecomer, vec![bet(bet_bear)])
+        .unwrap();
+
+    /* Capped at 90 seconds in total */
+    let status: StatusResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+    let bidding_round = status.bidding_round.unwrap();
+    assert_eq!(bidding_round.open_time, open_time.plus_seconds(90));
+    assert_eq!(bidding_round.extended_seconds, Uint128::new(90u128));
+
+    let fees: AccumulatedFeesResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr, &QueryMsg::AccumulatedFees {})
+        .unwrap();
+    assert_eq!(fees.amount, Uint128::new(8u128));
+}
+
+#[test]
+fn proper_settle_at_round_times() {
+    let mut router = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+
+    let bull = Addr::unchecked("bull");
+    let bear = Addr::unchecked("bear");
+
+    router.borrow_mut().init_modules(|router, _, storage| {
+        for account in [&bull, &bear] {
+            router
+                .bank
+                .init_balance(storage, account, coins(2000, SETTLE_DENOM))
+                .unwrap();
+        }
+    });
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        Some(Config {
+            settle_at_round_times: true,
+            price_tolerance_seconds: Uint128::new(60u128),
+            ..base_config()
+        }),
+        owner.clone(),
+        funds,
+    );
+
+    let config: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+    let execute = |msg: ExecuteMsg, funds: Vec<Coin>| -> CosmosMsg {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: prediction_market_addr.to_string(),
+            msg: to_binary(&msg).unwrap(),
+            funds,
+        })
+    };
+    let advance_to = |router: &mut App, seconds: u64| {
+        router.update_block(|block| {
+            block.time = Timestamp::from_seconds(seconds);
+            block.height += 1;
+        });
+    };
+    let query_round = |router: &App, round_id: u128| -> RoundResponse {
+        router
+            .wrap()
+            .query_wasm_smart(
+                prediction_market_addr.clone(),
+                &QueryMsg::FinishedRound {
+                    round_id: Uint128::new(round_id),
+                },
+            )
+            .unwrap()
+    };
+
+    /* Round 0 bids until 1200 and closes at 1800 */
+    router
+        .execute_multi(
+            bull,
+            vec![execute(
+                ExecuteMsg::BetBull {
+                    round_id: Uint128::zero(),
+                    referrer: None,
+                    min_multiplier: None,
+                    conditional: false,
+                },
+                coins(100, SETTLE_DENOM),
+            )],
+        )
+        .unwrap();
+    router
+        .execute_multi(
+            bear.clone(),
+            vec![execute(
+                ExecuteMsg::BetBear {
+                    round_id: Uint128::zero(),
+                    referrer: None,
+                    min_multiplier: None,
+                    conditional: false,
+                },
+                coins(100, SETTLE_DENOM),
+            )],
+        )
+        .unwrap();
+
+    /* The keeper is late, the price it sees is not the one at open_time */
+    advance_to(&mut router, 1190);
+    update_price(&mut router, config.clone(), Uint128::new(1_100_000), &owner);
+    advance_to(&mut router, 1300);
+    update_price(&mut router, config.clone(), Uint128::new(5_000_000), &owner);
+    router
+        .execute_multi(
+            owner.clone(),
+            vec![execute(ExecuteMsg::CloseRound {}, vec![])],
+        )
+        .unwrap();
+
+    let status: StatusResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+    let live_round = status.live_round.unwrap();
+    assert_eq!(live_round.open_price, Uint128::new(1_100_000));
+    assert_eq!(
+        live_round.open_price_time,
+        Some(Timestamp::from_seconds(1190))
+    );
+    assert_eq!(live_round.open_time, Timestamp::from_seconds(1200));
+    assert_eq!(live_round.close_time, Timestamp::from_seconds(1800));
+
+    advance_to(&mut router, 1790);
+    update_price(&mut router, config.clone(), Uint128::new(900_000), &owner);
+    advance_to(&mut router, 1900);
+    update_price(&mut router, config.clone(), Uint128::new(2_000_000), &owner);
+    router
+        .execute_multi(
+            owner.clone(),
+            vec![execute(ExecuteMsg::CloseRound {}, vec![])],
+        )
+        .unwrap();
+
+    let round = query_round(&router, 0);
+    assert_eq!(round.close_price, Uint128::new(900_000));
+    assert_eq!(round.close_price_time, Some(Timestamp::from_seconds(1790)));
+    assert_eq!(round.winner, Some(Direction::Bear));
+    assert!(!round.voided);
+
+    /* No update near 2400: round 1 closes voided, round 2 never goes live */
+    advance_to(&mut router, 3000);
+    router
+        .execute_multi(
+            owner.clone(),
+            vec![execute(ExecuteMsg::CloseRound {}, vec![])],
+        )
+        .unwrap();
+
+    let round = query_round(&router, 1);
+    assert!(round.voided);
+    assert_eq!(round.winner, None);
+    let round = query_round(&router, 2);
+    assert!(round.voided);
+    assert_eq!(round.open_price_time, None);
+
+    let status: StatusResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+    assert!(status.live_round.is_none());
+    let bidding_round = status.bidding_round.unwrap();
+    assert_eq!(bidding_round.id, Uint128::new(3));
+    assert_eq!(bidding_round.open_time, Timestamp::from_seconds(3600));
+
+    /* The winning bear still collects from the settled round */
+    router
+        .execute_multi(
+            bear,
+            vec![execute(
+                ExecuteMsg::CollectWinnings {
+                    rounds: vec![Uint128::zero()],
+                },
+                vec![],
+            )],
+        )
+        .unwrap();
+}
+
+#[test]
+fn proper_oracle_price_update_callback() {
+    let mut router = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        None,
+        owner.clone(),
+        funds,
+    );
+    let config: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+
+    let callback = |ticker: &str| ExecuteMsg::OraclePriceUpdate {
+        ticker: ticker.to_string(),
+        price: Uint128::new(1_000_000u128),
+        expo: 0,
+        timestamp: Timestamp::from_seconds(1200),
+    };
+
+    router.update_block(|block| {
+        block.time = block.time.plus_seconds(600);
+        block.height += 1;
+    });
+
+    /* Only the oracle can trigger a close */
+    router
+        .execute_contract(
+            owner.clone(),
+            prediction_market_addr.clone(),
+            &callback(DEFAULT_TICKER),
+            &[],
+        )
+        .unwrap_err();
+
+    /* Prices of other tickers are ignored */
+    router
+        .execute_contract(
+            config.fast_oracle_addr.clone(),
+            prediction_market_addr.clone(),
+            &callback("juno"),
+            &[],
+        )
+        .unwrap();
+    let status: StatusResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+    assert!(status.live_round.is_none());
+
+    router
+        .execute_contract(
+            config.fast_oracle_addr,
+            prediction_market_addr.clone(),
+            &callback(DEFAULT_TICKER),
+            &[],
+        )
+        .unwrap();
+    let status: StatusResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr, &QueryMsg::Status {})
+        .unwrap();
+    assert_eq!(status.live_round.unwrap().id, Uint128::zero());
+}
+
+#[test]
+fn proper_twap_settlement() {
+    let mut router = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let funds = coins(2000, SETTLE_DENOM);
+
+    let prediction_market_addr = create_market_and_start(
+        router.borrow_mut(),
+        Some(Config {
+            twap_window_seconds: Uint128::new(300u128),
+            ..base_config()
+        }),
+        owner.clone(),
+        funds,
+    );
+
+    let config: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.twap_window_seconds, Uint128::new(300u128));
+
+    let status: StatusResponse = router
+        .wrap()
+        .query_wasm_smart(prediction_market_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+    let live_round = status.live_round.unwrap();
+    assert_eq!(live_round.open_price, Uint128::new(1_000_000u128));
+
+    /* A spike just before the close barely moves the TWAP */
+    router.update_block(|block| {
+        block.time = live_round.close_time.minus_seconds(10);
+        block.height += 1;
+    });
+    update_price(&mut router, config, Uint128::new(3_000_000u128), &owner);
+    router.update_block(|block| {
+        block.time = live_round.close_time;
+        block.height += 1;
+    });
+    router
+        .execute_contract(
+            owner,
+            prediction_market_addr.clone(),
+            &ExecuteMsg::CloseRound {},
+            &[],
+        )
+        .unwrap();
+
+    let round: RoundResponse = router
+        .wrap()
+        .query_wasm_smart(
+            prediction_market_addr,
+            &QueryMsg::FinishedRound {
+                round_id: live_round.id,
+            },
+        )
+        .unwrap();
+    /* (1_000_000 * 290 + 3_000_000 * 10) / 300 */
+    assert_eq!(round.close_price, Uint128::new(1_066_666u128));
+    assert_eq!(round.winner, Some(Direction::Bull));
+}
+
+#[test]
+fn proper_config_validation() {
+    let config = base_config();
+    let instantiate = |config: Config| {
+        crate::contract::instantiate(
+            mock_dependencies().as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            InstantiateMsg {
+                config,
+                settle_denom: SETTLE_DENOM.to_string(),
+            },
+        )
+    };
+
+    instantiate(config.clone()).unwrap();
+
+    /* More than 100% of the staker fee would underflow on referred bets */
+    instantiate(Config {
+        referral_share: Uint128::new(10_001u128),
+        ..config.clone()
+    })
+    .unwrap_err();
+    let gold = FeeTier {
+        name: "gold".to_string(),
+        min_staked: Uint128::new(100u128),
+        burn_fee: Uint128::new(5_000u128),
+        staker_fee: Uint128::new(5_001u128),
+    };
+    instantiate(Config {
+        fee_tiers: vec![gold.clone()],
+        fee_tier_staking_addr: Some(Addr::unchecked("staking")),
+        ..config.clone()
+    })
+    .unwrap_err();
+
+    /* Tiers can't fall back to the rewards contract for balances */
+    instantiate(Config {
+        fee_tiers: vec![FeeTier {
+            staker_fee: Uint128::new(100u128),
+            ..gold
+        }],
+        ..config.clone()
+    })
+    .unwrap_err();
+
+    /* Reveals must fit in the live round */
+    let commit_reveal = |reveal_window_seconds: u128| Config {
+        commit_reveal: true,
+        reveal_window_seconds: Uint128::new(reveal_window_seconds),
+        ..config.clone()
+    };
+    instantiate(commit_reveal(300)).unwrap();
+    instantiate(commit_reveal(0)).unwrap_err();
+    instantiate(commit_reveal(600)).unwrap_err();
+
+    /* Configs that leave it out forfeit unrevealed commitments */
+    let json = String::from_utf8(
+        to_vec(&Config {
+            forfeit_unrevealed: false,
+            ..config
+        })
+        .unwrap(),
+    )
+    .unwrap()
+    .replace("\"forfeit_unrevealed\":false,", "");
+    let config: Config = from_slice(json.as_bytes()).unwrap();
+    assert!(config.forfeit_unrevealed);
+}
@@ -6,7 +6,7 @@
 
 import { CosmWasmClient, SigningCosmWasmClient, ExecuteResult } from "@cosmjs/cosmwasm-stargate";
 import { Coin, StdFee } from "@cosmjs/amino";
-import { Addr, Uint128, Config, ExecuteMsg, PartialConfig, InstantiateMsg, MyCurrentPositionResponse, QueryMsg, Timestamp, Uint64, StatusResponse, NextRound, LiveRound } from "./PricePrediction.types";
+import { Uint128, AccumulatedFeesResponse, Addr, RewardSink, Binary, Config, FeeTier, ExecuteMsg, Decimal, Direction, Timestamp, Uint64, PartialConfig, InstantiateMsg, MigrateMsg, MyCurrentPositionResponse, OddsResponse, QueryMsg, Referral, RoundPayoutsResponse, RoundSummary, SimulateBetResponse, SolvencyResponse, StatusResponse, NextRound, LiveRound, SudoMsg } from "./PricePrediction.types";
 export interface PricePredictionReadOnlyInterface {
   contractAddress: string;
   config: () => Promise<ConfigResponse>;
@@ -21,6 +21,39 @@ export interface PricePredictionReadOnlyInterface {
   }: {
     roundId: Uint128;
   }) => Promise<FinishedRoundResponse>;
+  accumulatedFees: () => Promise<AccumulatedFeesResponse>;
+  referrer: ({
+    address
+  }: {
+    address: string;
+  }) => Promise<ReferrerResponse>;
+  odds: ({
+    roundId
+  }: {
+    roundId: Uint128;
+  }) => Promise<OddsResponse>;
+  simulateBet: ({
+    address,
+    amount,
+    direction,
+    roundId
+  }: {
+    address?: string;
+    amount: Uint128;
+    direction: Direction;
+    roundId: Uint128;
+  }) => Promise<SimulateBetResponse>;
+  solvency: () => Promise<SolvencyResponse>;
+  roundPayouts: ({
+    roundId
+  }: {
+    roundId: Uint128;
+  }) => Promise<RoundPayoutsResponse>;
+  roundSummary: ({
+    roundId
+  }: {
+    roundId: Uint128;
+  }) => Promise<RoundSummaryResponse>;
 }
 export class PricePredictionQueryClient implements PricePredictionReadOnlyInterface {
   client: CosmWasmClient;
@@ -33,6 +66,13 @@ export class PricePredictionQueryClient implements PricePredictionReadOnlyInterf
     this.status = this.status.bind(this);
     this.myCurrentPosition = this.myCurrentPosition.bind(this);
     this.finishedRound = this.finishedRound.bind(this);
+    this.accumulatedFees = this.accumulatedFees.bind(this);
+    this.referrer = this.referrer.bind(this);
+    this.odds = this.odds.bind(this);
+    this.simulateBet = this.simulateBet.bind(this);
+    this.solvency = this.solvency.bind(this);
+    this.roundPayouts = this.roundPayouts.bind(this);
+    this.roundSummary = this.roundSummary.bind(this);
   }
 
   config = async (): Promise<ConfigResponse> => {
@@ -67,6 +107,80 @@ export class PricePredictionQueryClient implements PricePredictionReadOnlyInterf
       }
     });
   };
+  accumulatedFees = async (): Promise<AccumulatedFeesResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      accumulated_fees: {}
+    });
+  };
+  referrer = async ({
+    address
+  }: {
+    address: string;
+  }): Promise<ReferrerResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      referrer: {
+        address
+      }
+    });
+  };
+  odds = async ({
+    roundId
+  }: {
+    roundId: Uint128;
+  }): Promise<OddsResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      odds: {
+        round_id: roundId
+      }
+    });
+  };
+  simulateBet = async ({
+    address,
+    amount,
+    direction,
+    roundId
+  }: {
+    address?: string;
+    amount: Uint128;
+    direction: Direction;
+    roundId: Uint128;
+  }): Promise<SimulateBetResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      simulate_bet: {
+        address,
+        amount,
+        direction,
+        round_id: roundId
+      }
+    });
+  };
+  solvency = async (): Promise<SolvencyResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      solvency: {}
+    });
+  };
+  roundPayouts = async ({
+    roundId
+  }: {
+    roundId: Uint128;
+  }): Promise<RoundPayoutsResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      round_payouts: {
+        round_id: roundId
+      }
+    });
+  };
+  roundSummary = async ({
+    roundId
+  }: {
+    roundId: Uint128;
+  }): Promise<RoundSummaryResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      round_summary: {
+        round_id: roundId
+      }
+    });
+  };
 }
 export interface PricePredictionInterface extends PricePredictionReadOnlyInterface {
   contractAddress: string;
@@ -77,22 +191,88 @@ export interface PricePredictionInterface extends PricePredictionReadOnlyInterfa
     config: PartialConfig;
   }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
   betBull: ({
+    conditional,
+    minMultiplier,
+    referrer,
     roundId
   }: {
+    conditional?: boolean;
+    minMultiplier?: Decimal;
+    referrer?: string;
     roundId: Uint128;
   }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
   betBear: ({
+    conditional,
+    minMultiplier,
+    referrer,
     roundId
   }: {
+    conditional?: boolean;
+    minMultiplier?: Decimal;
+    referrer?: string;
     roundId: Uint128;
   }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  commitBet: ({
+    hash,
+    referrer,
+    roundId
+  }: {
+    hash: Binary;
+    referrer?: string;
+    roundId: Uint128;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  revealBet: ({
+    direction,
+    roundId,
+    salt
+  }: {
+    direction: Direction;
+    roundId: Uint128;
+    salt: string;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
   closeRound: (fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  oraclePriceUpdate: ({
+    expo,
+    price,
+    ticker,
+    timestamp
+  }: {
+    expo: number;
+    price: Uint128;
+    ticker: string;
+    timestamp: Timestamp;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
   collectWinnings: ({
     rounds
   }: {
     rounds: Uint128[];
   }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
   fundStakers: (fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  sweepDust: ({
+    roundIds
+  }: {
+    roundIds: Uint128[];
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  sweepExpired: ({
+    roundIds
+  }: {
+    roundIds: Uint128[];
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  prune: ({
+    beforeRound,
+    limit,
+    startAfter
+  }: {
+    beforeRound: Uint128;
+    limit?: number;
+    startAfter?: Uint128;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  migrateLegacy: ({
+    limit
+  }: {
+    limit?: number;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  claimReferralFees: (fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
   hault: (fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
   resume: (fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
 }
@@ -109,9 +289,17 @@ export class PricePredictionClient extends PricePredictionQueryClient implements
     this.updateConfig = this.updateConfig.bind(this);
     this.betBull = this.betBull.bind(this);
     this.betBear = this.betBear.bind(this);
+    this.commitBet = this.commitBet.bind(this);
+    this.revealBet = this.revealBet.bind(this);
     this.closeRound = this.closeRound.bind(this);
+    this.oraclePriceUpdate = this.oraclePriceUpdate.bind(this);
     this.collectWinnings = this.collectWinnings.bind(this);
     this.fundStakers = this.fundStakers.bind(this);
+    this.sweepDust = this.sweepDust.bind(this);
+    this.sweepExpired = this.sweepExpired.bind(this);
+    this.prune = this.prune.bind(this);
+    this.migrateLegacy = this.migrateLegacy.bind(this);
+    this.claimReferralFees = this.claimReferralFees.bind(this);
     this.hault = this.hault.bind(this);
     this.resume = this.resume.bind(this);
   }
@@ -128,32 +316,104 @@ export class PricePredictionClient extends PricePredictionQueryClient implements
     }, fee, memo, funds);
   };
   betBull = async ({
+    conditional,
+    minMultiplier,
+    referrer,
     roundId
   }: {
+    conditional?: boolean;
+    minMultiplier?: Decimal;
+    referrer?: string;
     roundId: Uint128;
   }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
     return await this.client.execute(this.sender, this.contractAddress, {
       bet_bull: {
+        conditional,
+        min_multiplier: minMultiplier,
+        referrer,
         round_id: roundId
       }
     }, fee, memo, funds);
   };
   betBear = async ({
+    conditional,
+    minMultiplier,
+    referrer,
     roundId
   }: {
+    conditional?: boolean;
+    minMultiplier?: Decimal;
+    referrer?: string;
     roundId: Uint128;
   }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
     return await this.client.execute(this.sender, this.contractAddress, {
       bet_bear: {
+        conditional,
+        min_multiplier: minMultiplier,
+        referrer,
+        round_id: roundId
+      }
+    }, fee, memo, funds);
+  };
+  commitBet = async ({
+    hash,
+    referrer,
+    roundId
+  }: {
+    hash: Binary;
+    referrer?: string;
+    roundId: Uint128;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      commit_bet: {
+        hash,
+        referrer,
         round_id: roundId
       }
     }, fee, memo, funds);
   };
+  revealBet = async ({
+    direction,
+    roundId,
+    salt
+  }: {
+    direction: Direction;
+    roundId: Uint128;
+    salt: string;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      reveal_bet: {
+        direction,
+        round_id: roundId,
+        salt
+      }
+    }, fee, memo, funds);
+  };
   closeRound = async (fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
     return await this.client.execute(this.sender, this.contractAddress, {
       close_round: {}
     }, fee, memo, funds);
   };
+  oraclePriceUpdate = async ({
+    expo,
+    price,
+    ticker,
+    timestamp
+  }: {
+    expo: number;
+    price: Uint128;
+    ticker: string;
+    timestamp: Timestamp;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      oracle_price_update: {
+        expo,
+        price,
+        ticker,
+        timestamp
+      }
+    }, fee, memo, funds);
+  };
   collectWinnings = async ({
     rounds
   }: {
@@ -170,6 +430,61 @@ export class PricePredictionClient extends PricePredictionQueryClient implements
       fund_stakers: {}
     }, fee, memo, funds);
   };
+  sweepDust = async ({
+    roundIds
+  }: {
+    roundIds: Uint128[];
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      sweep_dust: {
+        round_ids: roundIds
+      }
+    }, fee, memo, funds);
+  };
+  sweepExpired = async ({
+    roundIds
+  }: {
+    roundIds: Uint128[];
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      sweep_expired: {
+        round_ids: roundIds
+      }
+    }, fee, memo, funds);
+  };
+  prune = async ({
+    beforeRound,
+    limit,
+    startAfter
+  }: {
+    beforeRound: Uint128;
+    limit?: number;
+    startAfter?: Uint128;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      prune: {
+        before_round: beforeRound,
+        limit,
+        start_after: startAfter
+      }
+    }, fee, memo, funds);
+  };
+  migrateLegacy = async ({
+    limit
+  }: {
+    limit?: number;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      migrate_legacy: {
+        limit
+      }
+    }, fee, memo, funds);
+  };
+  claimReferralFees = async (fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      claim_referral_fees: {}
+    }, fee, memo, funds);
+  };
   hault = async (fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
     return await this.client.execute(this.sender, this.contractAddress, {
       hault: {}
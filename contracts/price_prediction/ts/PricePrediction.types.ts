@@ -4,15 +4,70 @@
 * and run the @cosmwasm/ts-codegen generate command to regenerate this file.
 */
 
-export type Addr = string;
 export type Uint128 = string;
+export interface AccumulatedFeesResponse {
+  amount: Uint128;
+  threshold: Uint128;
+  [k: string]: unknown;
+}
+export type Addr = string;
+export type RewardSink = {
+  stake_cw20_external_rewards: {
+    addr: Addr;
+    [k: string]: unknown;
+  };
+} | {
+  dao_treasury: {
+    addr: Addr;
+    [k: string]: unknown;
+  };
+} | {
+  cw20_stake: {
+    addr: Addr;
+    fund_msg: Binary;
+    [k: string]: unknown;
+  };
+} | {
+  address: {
+    addr: Addr;
+    [k: string]: unknown;
+  };
+};
+export type Binary = string;
 export interface Config {
+  auto_fund_stakers?: boolean;
   burn_addr: Addr;
   burn_fee: Uint128;
+  claim_expiry_rounds?: Uint128;
+  commit_reveal?: boolean;
   cw20_stake_external_rewards_addr: Addr;
   fast_oracle_addr: Addr;
+  fee_tier_staking_addr?: Addr | null;
+  fee_tier_whitelist?: Addr[];
+  fee_tiers?: FeeTier[];
+  forfeit_unrevealed?: boolean;
+  fund_stakers_threshold?: Uint128;
+  late_bet_surcharge?: Uint128;
+  max_snipe_extension_seconds?: Uint128;
   minimum_bet: Uint128;
   next_round_seconds: Uint128;
+  price_tolerance_seconds?: Uint128;
+  referral_share?: Uint128;
+  reveal_window_seconds?: Uint128;
+  reward_sink?: RewardSink | null;
+  settle_at_round_times?: boolean;
+  snipe_extension_seconds?: Uint128;
+  snipe_ratio_threshold?: Uint128;
+  snipe_window_seconds?: Uint128;
+  staker_fee: Uint128;
+  ticker?: string | null;
+  twap_window_seconds?: Uint128;
+  [k: string]: unknown;
+}
+export interface FeeTier {
+  burn_fee: Uint128;
+  min_staked: Uint128;
+  name: string;
   staker_fee: Uint128;
   [k: string]: unknown;
 }
@@ -23,18 +78,46 @@ export type ExecuteMsg = {
   };
 } | {
   bet_bull: {
+    conditional?: boolean;
+    min_multiplier?: Decimal | null;
+    referrer?: string | null;
     round_id: Uint128;
     [k: string]: unknown;
   };
 } | {
   bet_bear: {
+    conditional?: boolean;
+    min_multiplier?: Decimal | null;
+    referrer?: string | null;
+    round_id: Uint128;
+    [k: string]: unknown;
+  };
+} | {
+  commit_bet: {
+    hash: Binary;
+    referrer?: string | null;
+    round_id: Uint128;
+    [k: string]: unknown;
+  };
+} | {
+  reveal_bet: {
+    direction: Direction;
     round_id: Uint128;
+    salt: string;
     [k: string]: unknown;
   };
 } | {
   close_round: {
     [k: string]: unknown;
   };
+} | {
+  oracle_price_update: {
+    expo: number;
+    price: Uint128;
+    ticker: string;
+    timestamp: Timestamp;
+    [k: string]: unknown;
+  };
 } | {
   collect_winnings: {
     rounds: Uint128[];
@@ -44,6 +127,32 @@ export type ExecuteMsg = {
   fund_stakers: {
     [k: string]: unknown;
   };
+} | {
+  sweep_dust: {
+    round_ids: Uint128[];
+    [k: string]: unknown;
+  };
+} | {
+  sweep_expired: {
+    round_ids: Uint128[];
+    [k: string]: unknown;
+  };
+} | {
+  prune: {
+    before_round: Uint128;
+    limit?: number | null;
+    start_after?: Uint128 | null;
+    [k: string]: unknown;
+  };
+} | {
+  migrate_legacy: {
+    limit?: number | null;
+    [k: string]: unknown;
+  };
+} | {
+  claim_referral_fees: {
+    [k: string]: unknown;
+  };
 } | {
   hault: {
     [k: string]: unknown;
@@ -53,14 +162,38 @@ export type ExecuteMsg = {
     [k: string]: unknown;
   };
 };
+export type Decimal = string;
+export type Direction = "bull" | "bear";
+export type Timestamp = Uint64;
+export type Uint64 = string;
 export interface PartialConfig {
+  auto_fund_stakers?: boolean | null;
   burn_addr?: Addr | null;
   burn_fee?: Uint128 | null;
+  claim_expiry_rounds?: Uint128 | null;
+  commit_reveal?: boolean | null;
   cw20_stake_external_rewards_addr?: Addr | null;
   fast_oracle_addr?: Addr | null;
+  fee_tier_staking_addr?: Addr | null | null;
+  fee_tier_whitelist?: Addr[] | null;
+  fee_tiers?: FeeTier[] | null;
+  forfeit_unrevealed?: boolean | null;
+  fund_stakers_threshold?: Uint128 | null;
+  late_bet_surcharge?: Uint128 | null;
+  max_snipe_extension_seconds?: Uint128 | null;
   minimum_bet?: Uint128 | null;
   next_round_seconds?: Uint128 | null;
+  price_tolerance_seconds?: Uint128 | null;
+  referral_share?: Uint128 | null;
+  reveal_window_seconds?: Uint128 | null;
+  reward_sink?: RewardSink | null | null;
+  settle_at_round_times?: boolean | null;
+  snipe_extension_seconds?: Uint128 | null;
+  snipe_ratio_threshold?: Uint128 | null;
+  snipe_window_seconds?: Uint128 | null;
   staker_fee?: Uint128 | null;
+  ticker?: string | null;
+  twap_window_seconds?: Uint128 | null;
   [k: string]: unknown;
 }
 export interface InstantiateMsg {
@@ -68,6 +201,10 @@ export interface InstantiateMsg {
   settle_denom: string;
   [k: string]: unknown;
 }
+export interface MigrateMsg {
+  config?: PartialConfig | null;
+  [k: string]: unknown;
+}
 export interface MyCurrentPositionResponse {
   live_bear_amount: Uint128;
   live_bull_amount: Uint128;
@@ -75,6 +212,14 @@ export interface MyCurrentPositionResponse {
   next_bull_amount: Uint128;
   [k: string]: unknown;
 }
+export interface OddsResponse {
+  bear_amount: Uint128;
+  bear_multiplier: Decimal;
+  bull_amount: Uint128;
+  bull_multiplier: Decimal;
+  round_id: Uint128;
+  [k: string]: unknown;
+}
 export type QueryMsg = {
   config: {
     [k: string]: unknown;
@@ -93,9 +238,91 @@ export type QueryMsg = {
     round_id: Uint128;
     [k: string]: unknown;
   };
+} | {
+  accumulated_fees: {
+    [k: string]: unknown;
+  };
+} | {
+  referrer: {
+    address: string;
+    [k: string]: unknown;
+  };
+} | {
+  odds: {
+    round_id: Uint128;
+    [k: string]: unknown;
+  };
+} | {
+  simulate_bet: {
+    address?: string | null;
+    amount: Uint128;
+    direction: Direction;
+    round_id: Uint128;
+    [k: string]: unknown;
+  };
+} | {
+  solvency: {
+    [k: string]: unknown;
+  };
+} | {
+  round_payouts: {
+    round_id: Uint128;
+    [k: string]: unknown;
+  };
+} | {
+  round_summary: {
+    round_id: Uint128;
+    [k: string]: unknown;
+  };
 };
-export type Timestamp = Uint64;
-export type Uint64 = string;
+export interface Referral {
+  bets: number;
+  total_earned: Uint128;
+  unclaimed: Uint128;
+  volume: Uint128;
+  [k: string]: unknown;
+}
+export interface RoundPayoutsResponse {
+  claimed_shares: Uint128;
+  dust?: Uint128 | null;
+  dust_swept: Uint128;
+  expired: boolean;
+  paid_out: Uint128;
+  round_id: Uint128;
+  unclaimed_swept: Uint128;
+  winning_shares: Uint128;
+  [k: string]: unknown;
+}
+export interface RoundSummary {
+  bear_amount: Uint128;
+  bull_amount: Uint128;
+  close_price: Uint128;
+  id: Uint128;
+  open_price: Uint128;
+  voided?: boolean;
+  winner?: Direction | null;
+  [k: string]: unknown;
+}
+export interface SimulateBetResponse {
+  burn_fee: Uint128;
+  late_surcharge: Uint128;
+  multiplier: Decimal;
+  net_amount: Uint128;
+  payout: Uint128;
+  staker_fee: Uint128;
+  [k: string]: unknown;
+}
+export interface SolvencyResponse {
+  accumulated_fee: Uint128;
+  balance: Uint128;
+  bet_liability: Uint128;
+  deficit: Uint128;
+  escrow_liability: Uint128;
+  referral_liability: Uint128;
+  surplus: Uint128;
+  total_liabilities: Uint128;
+  [k: string]: unknown;
+}
 export interface StatusResponse {
   bidding_round?: NextRound | null;
   live_round?: LiveRound | null;
@@ -106,6 +333,8 @@ export interface NextRound {
   bid_time: Timestamp;
   bull_amount: Uint128;
   close_time: Timestamp;
+  conditional_bets?: number;
+  extended_seconds?: Uint128;
   id: Uint128;
   open_time: Timestamp;
   [k: string]: unknown;
@@ -117,6 +346,27 @@ export interface LiveRound {
   close_time: Timestamp;
   id: Uint128;
   open_price: Uint128;
+  open_price_time?: Timestamp | null;
   open_time: Timestamp;
+  price_expo?: number;
   [k: string]: unknown;
-}
\ No newline at end of file
+}
+export type SudoMsg = {
+  clock_end_block: {
+    [k: string]: unknown;
+  };
+} | {
+  tick: {
+    [k: string]: unknown;
+  };
+} | {
+  force_halt: {
+    halted: boolean;
+    [k: string]: unknown;
+  };
+} | {
+  force_config: {
+    config: PartialConfig;
+    [k: string]: unknown;
+  };
+};
\ No newline at end of file
@@ -5,7 +5,7 @@ for (const c of [ "price_prediction", "fast_oracle" ]) {
   codegen({
     contracts: [
       {
-        name: 'price_prediction',
+        name: c,
         dir: `../contracts/${c}/schema`,
       },
     ],
@@ -1,25 +1,345 @@
+use cosmwasm_std::{Addr, Binary, Decimal, Timestamp, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-pub mod msg {
-    use cosmwasm_std::{Addr, Uint128};
+/* Ticker the single price of pre-ticker oracles is migrated to */
+pub const DEFAULT_TICKER: &str = "default";
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PriceEntry {
+    pub price: Uint128,
+    pub updated_at: Timestamp,
+    pub height: u64,
+    /* Feeder that submitted the price or, for a published median, whose
+     * submission completed it; the other submissions are not recorded */
+    pub triggered_by: Addr,
+    /* Exponent of the feed when the price was published */
+    #[serde(default)]
+    pub expo: i32,
+}
+
+/**
+ * What a ticker's prices mean, a price is value * 10^expo quote per base
+ */
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct FeedInfo {
+    pub expo: i32,
+    pub description: String,
+    pub base: String,
+    pub quote: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Config {
+    /* Fresh feeder submissions needed before a ticker has a price */
+    pub quorum: u32,
+    /* Submissions older than this are ignored, zero keeps them forever */
+    pub max_submission_age_seconds: Uint128,
+    /* Moves beyond this share of the last price need a confirmation */
+    #[serde(default)]
+    pub max_deviation: Decimal,
+    /* Time a feeder has to wait between two updates of a ticker */
+    #[serde(default)]
+    pub min_update_interval_seconds: Uint128,
+}
+
+/**
+ * Update held back by the deviation guard until confirmed
+ */
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PendingPrice {
+    pub price: Uint128,
+    pub previous_price: Uint128,
+    pub feeder: Addr,
+    pub submitted_at: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyScheme {
+    /* Signs the sha256 of the payload */
+    Secp256k1,
+    Ed25519,
+}
 
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PublisherKey {
+    pub pubkey: Binary,
+    pub scheme: KeyScheme,
+}
+
+/**
+ * JSON signed by a feeder's publisher key, relayed with SubmitSigned
+ */
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PricePayload {
+    pub publisher: Addr,
+    /* Chain and oracle the payload is meant for, so it can't be replayed */
+    pub chain_id: String,
+    pub contract: Addr,
+    pub ticker: String,
+    pub price: Uint128,
+    pub timestamp: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Subscriber {
+    pub contract: Addr,
+    /* Tickers to be notified about, empty for all of them */
+    pub tickers: Vec<String>,
+    /* Failing to stay under it only fails the callback, callbacks without
+     * one get a default limit so no subscriber can use up an update's gas */
+    pub gas_limit: Option<u64>,
+}
+
+pub mod msg {
     use super::*;
 
     #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
     #[serde(rename_all = "snake_case")]
     pub struct InstantiateMsg {}
 
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct MigrateMsg {}
+
     #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
     #[serde(rename_all = "snake_case")]
     pub enum ExecuteMsg {
-        Update { price: Uint128 },
-        Owner { owner: Addr },
+        Update {
+            ticker: String,
+            price: Uint128,
+        },
+        /* The new owner takes over once it accepts */
+        ProposeOwner {
+            owner: Addr,
+        },
+        AcceptOwnership {},
+        /* Withdraw a proposal nobody accepted yet */
+        CancelOwnershipProposal {},
+        AddFeeder {
+            feeder: Addr,
+        },
+        RemoveFeeder {
+            feeder: Addr,
+        },
+        UpdateConfig {
+            quorum: Option<u32>,
+            max_submission_age_seconds: Option<Uint128>,
+            max_deviation: Option<Decimal>,
+            min_update_interval_seconds: Option<Uint128>,
+        },
+        /* Publish the update `feeder` has waiting for `ticker` */
+        ConfirmPrice {
+            ticker: String,
+            feeder: Addr,
+        },
+        SetPublisherKey {
+            feeder: Addr,
+            key: PublisherKey,
+        },
+        RemovePublisherKey {
+            feeder: Addr,
+        },
+        /* Open to anyone, `payload` is a JSON encoded PricePayload */
+        SubmitSigned {
+            payload: Binary,
+            signature: Binary,
+        },
+        SetFeedInfo {
+            ticker: String,
+            info: FeedInfo,
+        },
+        AddSubscriber {
+            contract: Addr,
+            tickers: Vec<String>,
+            gas_limit: Option<u64>,
+        },
+        RemoveSubscriber {
+            contract: Addr,
+        },
+    }
+
+    /**
+     * Executed on subscribers whenever a price is published
+     */
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum CallbackMsg {
+        OraclePriceUpdate {
+            ticker: String,
+            price: Uint128,
+            expo: i32,
+            timestamp: Timestamp,
+        },
     }
 
     #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
     #[serde(rename_all = "snake_case")]
     pub enum QueryMsg {
-        Price {},
+        Price {
+            ticker: String,
+        },
+        AllPrices {
+            start_after: Option<String>,
+            limit: Option<u32>,
+        },
+        /* Last price published at or before `time` */
+        PriceAt {
+            ticker: String,
+            time: Timestamp,
+        },
+        /* Published medians, the last one of a block replaces the others */
+        PriceHistory {
+            ticker: String,
+            start_after: Option<Timestamp>,
+            limit: Option<u32>,
+        },
+        /* Time-weighted average price over [start, end] */
+        Twap {
+            ticker: String,
+            start: Timestamp,
+            end: Timestamp,
+        },
+        /* OHLC candles of `interval` seconds, oldest first */
+        Candles {
+            ticker: String,
+            interval: u64,
+            start_after: Option<Timestamp>,
+            limit: Option<u32>,
+        },
+        Config {},
+        Owner {},
+        PendingOwner {},
+        Feeders {},
+        /* Updates of `ticker` waiting for a confirmation, one per feeder */
+        PendingPrice {
+            ticker: String,
+        },
+        PublisherKey {
+            feeder: Addr,
+        },
+        FeedInfo {
+            ticker: String,
+        },
+        Subscribers {},
+    }
+}
+
+pub mod response {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct TickerPrice {
+        pub ticker: String,
+        pub price: Uint128,
+        pub expo: i32,
+        pub updated_at: Timestamp,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct PriceResponse {
+        pub value: Uint128,
+        pub expo: i32,
+        /* When the price was last published */
+        pub timestamp: Timestamp,
+    }
+
+    pub type FeedInfoResponse = FeedInfo;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct SubscribersResponse {
+        pub subscribers: Vec<Subscriber>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct AllPricesResponse {
+        pub prices: Vec<TickerPrice>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct PriceAtResponse {
+        /* None before the ticker's first price */
+        pub entry: Option<PriceEntry>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct PriceHistoryResponse {
+        pub ticker: String,
+        pub entries: Vec<PriceEntry>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct TwapResponse {
+        pub ticker: String,
+        /* None when nothing was published at or before `start` */
+        pub twap: Option<Uint128>,
+        pub expo: i32,
+        pub start: Timestamp,
+        pub end: Timestamp,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct Candle {
+        pub open_time: Timestamp,
+        pub open: Uint128,
+        pub high: Uint128,
+        pub low: Uint128,
+        pub close: Uint128,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct CandlesResponse {
+        pub ticker: String,
+        pub interval: u64,
+        pub candles: Vec<Candle>,
+    }
+
+    pub type ConfigResponse = Config;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct OwnerResponse {
+        pub owner: Addr,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct PendingOwnerResponse {
+        pub pending_owner: Option<Addr>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct FeedersResponse {
+        pub feeders: Vec<Addr>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct PendingPriceResponse {
+        pub pending: Vec<PendingPrice>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct PublisherKeyResponse {
+        pub key: Option<PublisherKey>,
     }
 }
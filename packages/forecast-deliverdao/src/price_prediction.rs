@@ -1,11 +1,11 @@
-use cosmwasm_std::{Addr, Timestamp, Uint128};
+use cosmwasm_std::{Addr, Binary, Decimal, Timestamp, Uint128};
 use partial_derive::Partial;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 pub const FEE_PRECISION: u128 = 100u128;
 
-#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Direction {
     Bull,
@@ -13,7 +13,11 @@ pub enum Direction {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /* Config fields to change along with the upgrade */
+    #[serde(default)]
+    pub config: Option<PartialConfig>,
+}
 
 impl ToString for Direction {
     fn to_string(&self) -> String {
@@ -40,6 +44,112 @@ pub struct Config {
     pub burn_fee: Uint128,
     pub burn_addr: Addr,
     pub staker_fee: Uint128,
+    /* FundStakers is skipped until the accumulated fee reaches this amount */
+    #[serde(default)]
+    pub fund_stakers_threshold: Uint128,
+    /* CloseRound funds the stakers once the threshold has been crossed */
+    #[serde(default)]
+    pub auto_fund_stakers: bool,
+    /* Where FundStakers sends the staker fee; defaults to the stake-cw20
+     * external rewards contract above */
+    #[serde(default)]
+    pub reward_sink: Option<RewardSink>,
+    /* Part of the staker fee credited to a bet's referrer, with the same
+     * precision as the fees */
+    #[serde(default)]
+    pub referral_share: Uint128,
+    /* Discounted fees for bettors with enough stake */
+    #[serde(default)]
+    pub fee_tiers: Vec<FeeTier>,
+    /* Accounts that always get the cheapest fee tier */
+    #[serde(default)]
+    pub fee_tier_whitelist: Vec<Addr>,
+    /* stake-cw20 contract queried for stake, required with fee tiers */
+    #[serde(default)]
+    pub fee_tier_staking_addr: Option<Addr>,
+    /* Later rounds that can finish before a round's winnings expire;
+     * zero keeps them claimable forever */
+    #[serde(default)]
+    pub claim_expiry_rounds: Uint128,
+    /* Bets are committed as hashes and revealed once the round opens */
+    #[serde(default)]
+    pub commit_reveal: bool,
+    /* Seconds after open_time during which commitments can be revealed */
+    #[serde(default)]
+    pub reveal_window_seconds: Uint128,
+    /* Unrevealed commitments go to the staker fee instead of being
+     * refunded; refunds give bettors a free option on the open price.
+     * Voided rounds always refund */
+    #[serde(default = "default_forfeit_unrevealed")]
+    pub forfeit_unrevealed: bool,
+    /* Final seconds before open_time in which bets count as late */
+    #[serde(default)]
+    pub snipe_window_seconds: Uint128,
+    /* A late bet moving the bull share of the pool by more than this,
+     * with the same precision as the fees, delays open_time */
+    #[serde(default)]
+    pub snipe_ratio_threshold: Uint128,
+    #[serde(default)]
+    pub snipe_extension_seconds: Uint128,
+    /* Total delay a round can get from late bets */
+    #[serde(default)]
+    pub max_snipe_extension_seconds: Uint128,
+    /* Extra staker fee paid by late bets */
+    #[serde(default)]
+    pub late_bet_surcharge: Uint128,
+    /* Oracle ticker rounds settle on, the oracle's default when unset */
+    #[serde(default)]
+    pub ticker: Option<String>,
+    /* Settle on the oracle price as of open_time and close_time */
+    #[serde(default)]
+    pub settle_at_round_times: bool,
+    /* How stale that price may be before the round is voided */
+    #[serde(default)]
+    pub price_tolerance_seconds: Uint128,
+    /* Settle on the oracle TWAP over this many seconds, 0 for one reading */
+    #[serde(default)]
+    pub twap_window_seconds: Uint128,
+}
+
+fn default_forfeit_unrevealed() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct FeeTier {
+    pub name: String,
+    /* Staked balance needed to qualify */
+    pub min_staked: Uint128,
+    pub burn_fee: Uint128,
+    pub staker_fee: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/**
+ * Destinations the accumulated staker fee can be sent to
+ */
+pub enum RewardSink {
+    /* stake-cw20 external rewards, funded with `{"fund":{}}` */
+    StakeCw20ExternalRewards { addr: Addr },
+    /* DAO DAO treasury / core contract, funded with a bank send */
+    DaoTreasury { addr: Addr },
+    /* cw20-stake contract funded with its own execute msg */
+    Cw20Stake { addr: Addr, fund_msg: Binary },
+    /* Plain address, funded with a bank send */
+    Address { addr: Addr },
+}
+
+impl RewardSink {
+    pub fn addr(&self) -> &Addr {
+        match self {
+            RewardSink::StakeCw20ExternalRewards { addr }
+            | RewardSink::DaoTreasury { addr }
+            | RewardSink::Cw20Stake { addr, .. }
+            | RewardSink::Address { addr } => addr,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
@@ -51,6 +161,12 @@ pub struct NextRound {
     pub close_time: Timestamp,
     pub bull_amount: Uint128,
     pub bear_amount: Uint128,
+    /* How far late bets have pushed back open_time */
+    #[serde(default)]
+    pub extended_seconds: Uint128,
+    /* Conditional bets escrowed for the round */
+    #[serde(default)]
+    pub conditional_bets: u32,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
@@ -63,6 +179,12 @@ pub struct LiveRound {
     pub open_price: Uint128,
     pub bull_amount: Uint128,
     pub bear_amount: Uint128,
+    /* Oracle timestamp of open_price when settling at round times */
+    #[serde(default)]
+    pub open_price_time: Option<Timestamp>,
+    /* Oracle exponent the round's prices are expressed with */
+    #[serde(default)]
+    pub price_expo: i32,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
@@ -77,6 +199,144 @@ pub struct FinishedRound {
     pub winner: Option<Direction>,
     pub bull_amount: Uint128,
     pub bear_amount: Uint128,
+    #[serde(default)]
+    pub open_price_time: Option<Timestamp>,
+    #[serde(default)]
+    pub close_price_time: Option<Timestamp>,
+    /* No oracle price within tolerance, every bet is refunded */
+    #[serde(default)]
+    pub voided: bool,
+    #[serde(default)]
+    pub price_expo: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/**
+ * Outcome kept for a round once Prune removed its full state
+ */
+pub struct RoundSummary {
+    pub id: Uint128,
+    pub open_price: Uint128,
+    pub close_price: Uint128,
+    pub winner: Option<Direction>,
+    pub bull_amount: Uint128,
+    pub bear_amount: Uint128,
+    #[serde(default)]
+    pub voided: bool,
+}
+
+impl From<FinishedRound> for RoundSummary {
+    fn from(round: FinishedRound) -> Self {
+        RoundSummary {
+            id: round.id,
+            open_price: round.open_price,
+            close_price: round.close_price,
+            winner: round.winner,
+            bull_amount: round.bull_amount,
+            bear_amount: round.bear_amount,
+            voided: round.voided,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Referral {
+    /* Referral fees that can be claimed with ClaimReferralFees */
+    pub unclaimed: Uint128,
+    pub total_earned: Uint128,
+    /* Gross amount bet through this referrer */
+    pub volume: Uint128,
+    pub bets: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Bet {
+    pub direction: Direction,
+    /* Net of fees */
+    pub amount: Uint128,
+    pub placed_at: Timestamp,
+    /* Set once the winnings or refund have been collected */
+    pub claimed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Commitment {
+    /* sha256 of "<round_id>:<bull|bear>:<salt>:<sender>" */
+    pub hash: Binary,
+    /* Net of fees */
+    pub amount: Uint128,
+    /* Paid with the commitment, checked against the minimum bet on reveal */
+    pub gross_amount: Uint128,
+    pub committed_at: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ConditionalBet {
+    pub direction: Direction,
+    /* Net of fees */
+    pub amount: Uint128,
+    pub min_multiplier: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/**
+ * Running totals of what the contract owes, besides the staker fee
+ */
+pub struct Liabilities {
+    /* Bets in open pools plus unclaimed winnings and refunds */
+    pub bets: Uint128,
+    /* Conditional bets waiting for their round to open */
+    pub escrow: Uint128,
+    /* Referral fees not yet claimed */
+    pub referral_fees: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/**
+ * Progress of rebuilding the state of a pre-0.2.0 contract, continued by
+ * MigrateLegacy one batch at a time
+ */
+pub enum LegacyMigration {
+    /*
+     * Moving the per-direction bets into BETS, bets of rounds that are
+     * gone count as placed at `migrated_at`
+     */
+    Bets {
+        migrated_at: Timestamp,
+    },
+    /* Summing what is still owed over the bets after `start_after` */
+    Liabilities {
+        start_after: Option<(Uint128, Addr)>,
+        liabilities: Liabilities,
+    },
+    /* Recording what the finished rounds after `start_after` paid out */
+    Payouts {
+        start_after: Option<Uint128>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/**
+ * Claims made against a finished round's winning side
+ */
+pub struct RoundPayouts {
+    /* Winning side shares that have been collected */
+    pub claimed_shares: Uint128,
+    /* Sum of the rounded down payouts for those shares */
+    pub paid_out: Uint128,
+    /* Remainder moved to the staker fee by SweepDust */
+    pub dust_swept: Uint128,
+    /* Expired winnings moved to the staker fee by SweepExpired */
+    #[serde(default)]
+    pub unclaimed_swept: Uint128,
 }
 
 pub mod msg {
@@ -91,6 +351,7 @@ pub mod msg {
         pub settle_denom: String,
     }
 
+    #[allow(clippy::large_enum_variant)]
     #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
     #[serde(rename_all = "snake_case")]
     pub enum ExecuteMsg {
@@ -106,6 +367,14 @@ pub mod msg {
         BetBull {
             /* In case the TX is delayed */
             round_id: Uint128,
+            /* Frontend or account credited with part of the staker fee */
+            referrer: Option<String>,
+            /* Refuse the bet if its payout multiplier would be lower */
+            min_multiplier: Option<Decimal>,
+            /* Escrow the bet and only count it if the odds still meet
+             * min_multiplier when the round opens, otherwise refund it */
+            #[serde(default)]
+            conditional: bool,
         },
         /**
          * Price go down
@@ -113,12 +382,47 @@ pub mod msg {
         BetBear {
             /* In case the TX is delayed */
             round_id: Uint128,
+            /* Frontend or account credited with part of the staker fee */
+            referrer: Option<String>,
+            /* Refuse the bet if its payout multiplier would be lower */
+            min_multiplier: Option<Decimal>,
+            /* Escrow the bet and only count it if the odds still meet
+             * min_multiplier when the round opens, otherwise refund it */
+            #[serde(default)]
+            conditional: bool,
+        },
+        /**
+         * Bet without revealing the direction, in commit-reveal mode.
+         * `hash` is the sha256 of "<round_id>:<bull|bear>:<salt>:<sender>"
+         */
+        CommitBet {
+            round_id: Uint128,
+            hash: Binary,
+            /* Frontend or account credited with part of the staker fee */
+            referrer: Option<String>,
+        },
+        /**
+         * Reveal a committed bet during the reveal window after open_time
+         */
+        RevealBet {
+            round_id: Uint128,
+            direction: Direction,
+            salt: String,
         },
         /**
          * Permissionless msg to close the current round and open the next
          * NOTE It is permissionless because we can check timestamps :)
          */
         CloseRound {},
+        /**
+         * Fast oracle subscriber callback, closes the round when it is due
+         */
+        OraclePriceUpdate {
+            ticker: String,
+            price: Uint128,
+            expo: i32,
+            timestamp: Timestamp,
+        },
         /**
          * Settle winnings for an account
          */
@@ -126,17 +430,111 @@ pub mod msg {
             rounds: Vec<Uint128>,
         },
         FundStakers {},
+        /**
+         * Permissionless msg moving the rounding remainder of fully claimed
+         * rounds to the staker fee
+         */
+        SweepDust {
+            round_ids: Vec<Uint128>,
+        },
+        /**
+         * Permissionless msg moving the unclaimed winnings of expired rounds
+         * to the staker fee and pruning their bets
+         */
+        SweepExpired {
+            round_ids: Vec<Uint128>,
+        },
+        /**
+         * Permissionless msg archiving settled rounds after `start_after`
+         * and older than `before_round` and deleting their bets, `limit`
+         * bounds the number of rounds and entries removed. Rounds that
+         * still owe winnings are skipped without using up the limit
+         */
+        Prune {
+            before_round: Uint128,
+            start_after: Option<Uint128>,
+            limit: Option<u32>,
+        },
+        /**
+         * Permissionless msg continuing the migration of a pre-0.2.0
+         * contract, `limit` bounds the number of entries touched. Bets and
+         * claims are refused until it is done
+         */
+        MigrateLegacy {
+            limit: Option<u32>,
+        },
+        /**
+         * Withdraw the referral fees earned by the sender
+         */
+        ClaimReferralFees {},
         Hault {},
         Resume {},
     }
 
+    /**
+     * Messages only the chain can send, from a clock module or governance
+     */
+    #[allow(clippy::large_enum_variant)]
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum SudoMsg {
+        /* Sent by the clock module at the end of every block */
+        ClockEndBlock {},
+        /* Same as ClockEndBlock, for other chain schedulers */
+        Tick {},
+        /* Hault or resume the games without the contract admin */
+        ForceHalt { halted: bool },
+        /* Update the config without the contract admin */
+        ForceConfig { config: PartialConfig },
+    }
+
     #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
     #[serde(rename_all = "snake_case")]
     pub enum QueryMsg {
         Config {},
         Status {},
-        MyCurrentPosition { address: String },
-        FinishedRound { round_id: Uint128 },
+        MyCurrentPosition {
+            address: String,
+        },
+        FinishedRound {
+            round_id: Uint128,
+        },
+        AccumulatedFees {},
+        Referrer {
+            address: String,
+        },
+        /**
+         * Payout multipliers of the bidding or live round
+         */
+        Odds {
+            round_id: Uint128,
+        },
+        /**
+         * Net stake and payout of a bet if the round closed right after it
+         */
+        SimulateBet {
+            round_id: Uint128,
+            direction: Direction,
+            amount: Uint128,
+            /* Bettor whose fee tier applies, base fees when unset */
+            address: Option<String>,
+        },
+        /**
+         * Compare the settle denom balance against everything owed
+         */
+        Solvency {},
+        /**
+         * Claimed winnings and rounding remainder of a finished round
+         */
+        RoundPayouts {
+            round_id: Uint128,
+        },
+        /**
+         * Outcome of a finished round, including pruned ones
+         */
+        RoundSummary {
+            round_id: Uint128,
+        },
     }
 }
 
@@ -147,6 +545,66 @@ pub mod response {
 
     pub type RoundResponse = FinishedRound;
 
+    pub type RoundSummaryResponse = RoundSummary;
+
+    pub type ReferrerResponse = Referral;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct OddsResponse {
+        pub round_id: Uint128,
+        pub bull_amount: Uint128,
+        pub bear_amount: Uint128,
+        /* Payout per unit bet if that side wins */
+        pub bull_multiplier: Decimal,
+        pub bear_multiplier: Decimal,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct SimulateBetResponse {
+        pub burn_fee: Uint128,
+        pub staker_fee: Uint128,
+        /* Extra staker fee of bets in the snipe window */
+        pub late_surcharge: Uint128,
+        /* What is added to the pool after fees */
+        pub net_amount: Uint128,
+        /* Multiplier for the bet's side including the bet itself */
+        pub multiplier: Decimal,
+        pub payout: Uint128,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct SolvencyResponse {
+        pub balance: Uint128,
+        pub bet_liability: Uint128,
+        pub escrow_liability: Uint128,
+        pub referral_liability: Uint128,
+        pub accumulated_fee: Uint128,
+        pub total_liabilities: Uint128,
+        /* Balance above the liabilities, e.g. rounding dust */
+        pub surplus: Uint128,
+        /* Liabilities not covered by the balance */
+        pub deficit: Uint128,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct RoundPayoutsResponse {
+        pub round_id: Uint128,
+        /* Shares on the winning side, zero for ties and refunded rounds */
+        pub winning_shares: Uint128,
+        pub claimed_shares: Uint128,
+        pub paid_out: Uint128,
+        /* Remainder left by rounding, known once every winner claimed */
+        pub dust: Option<Uint128>,
+        pub dust_swept: Uint128,
+        /* Winnings can no longer be collected */
+        pub expired: bool,
+        pub unclaimed_swept: Uint128,
+    }
+
     #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
     #[serde(rename_all = "snake_case")]
     pub struct StatusResponse {
@@ -162,4 +620,12 @@ pub mod response {
         pub next_bear_amount: Uint128,
         pub next_bull_amount: Uint128,
     }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct AccumulatedFeesResponse {
+        /* Staker fees waiting to be sent with FundStakers */
+        pub amount: Uint128,
+        pub threshold: Uint128,
+    }
 }